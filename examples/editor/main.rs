@@ -37,11 +37,8 @@ impl Program for App {
     }
 
     fn input(&mut self, input: Input) {
-        match input {
-            Input::KeyDown(Scancode::Q) => {
-                self.shutdown = true;
-            }
-            _ => {}
+        if let Input::KeyDown(Scancode::Q) = input {
+            self.shutdown = true;
         }
     }
 }
@@ -97,6 +94,7 @@ fn render_frame_size_warning(frame: &mut Frame) {
 
 
 
+#[allow(dead_code)] // TODO: Render the content at the cursor position.
 struct Editor {
     content: String,
     cursor_pos: (u16, u16),
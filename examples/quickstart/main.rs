@@ -46,11 +46,8 @@ impl Program for MyApp {
     }
 
     fn input(&mut self, input: Input) {
-        match input {
-            Input::KeyDown(Scancode::Q) => {
-                self.shutdown = true;
-            }
-            _ => {}
+        if let Input::KeyDown(Scancode::Q) = input {
+            self.shutdown = true;
         }
     }
 }
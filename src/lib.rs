@@ -7,6 +7,9 @@ pub mod program;
 pub mod rendering;
 pub mod types;
 
+#[cfg(test)]
+mod test_utils;
+
 pub use platform::*;
 pub use program::*;
 pub use rendering::*;
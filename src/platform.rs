@@ -3,7 +3,9 @@
 
 
 mod terminal;
+mod waker;
 pub use terminal::*;
+pub use waker::*;
 
 use crate::Program;
 
@@ -4,21 +4,42 @@
 
 
 
-use std::io::Write as _;
+use std::{
+    io::Write as _,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
 
 use crossterm::{
     event::{
         KeyCode, KeyEvent, KeyEventKind, KeyModifiers, ModifierKeyCode, MouseEvent, MouseEventKind,
     },
     queue,
-    style::{Attribute, Color as CtColor, SetAttribute},
+    style::{Attribute, Color as CtColor, SetAttribute, SetBackgroundColor, SetUnderlineColor},
     ExecutableCommand as _,
 };
+use unicode_width::UnicodeWidthStr as _;
 
 use crate::{
-    Area, Buffer, Color, Command, CursorStyle, Frame, Input, Modifier, MouseButton, Program, Scancode,
+    Area, Buffer, Cell, Color, Command, CursorStyle, Damage, Frame, Input, Layers, Modifier,
+    MouseButton, Program, RenderMode, Scancode, Theme, Waker,
 };
 
+use super::waker::{self, Signal};
+
+
+
+/// How long to wait for input between frames when rendering continuously.
+const FRAME_INTERVAL: Duration = Frame::ANIMATION_INTERVAL;
+/// The longest to wait for input when rendering on demand, with no redraw scheduled.
+const IDLE_INTERVAL: Duration = Duration::from_secs(60);
+/// How often the input thread checks whether it should stop.
+const INPUT_INTERVAL: Duration = Duration::from_millis(50);
+
 
 
 /// Run a dreg program inside a terminal emulator.
@@ -28,130 +49,268 @@ pub struct Terminal {
     buffers: [Buffer; 2],
     /// The index of the current buffer in the previous array.
     current: usize,
+    /// The layers drawn above the current buffer at the end of each render pass.
+    layers: Layers,
+    /// The theme passed to the program with each frame.
+    theme: Theme,
+    /// The regions of the current buffer that need to be redrawn.
+    damage: Damage,
+    /// A copy of the current buffer from before the layers were drawn onto it, used as the
+    /// starting point for the next render pass by programs with retained rendering.
+    retained: Buffer,
+    /// Whether any layers were drawn in the last render pass.
+    layered: bool,
+    /// The clear color the screen was last cleared with, or `None` if it hasn't been cleared.
+    clear_color: Option<Color>,
+    /// The indices of the cells that changed in the last render pass. Kept around so that the
+    /// allocation can be reused.
+    updates: Vec<usize>,
+    /// Used to deliver input and messages to the event loop.
+    waker: Waker,
+    /// Receives everything sent through the terminal's [`Waker`]s.
+    signals: mpsc::Receiver<Signal>,
+    /// Everything to be written to the terminal at the end of a render pass, which is written all
+    /// at once.
+    output: Vec<u8>,
+    synchronized_output: SynchronizedOutput,
+    extended_underlines: ExtendedUnderlines,
     last_known_size: (u16, u16),
 }
 
+/// Whether a [`Terminal`] wraps each frame in synchronized update sequences (DEC private mode
+/// 2026).
+///
+/// Terminals that support synchronized output hold off on displaying anything written between the
+/// two sequences until the second one arrives, so that partially drawn frames are never shown.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SynchronizedOutput {
+    /// Ask the terminal whether it supports synchronized output (with DECRQM) on startup.
+    #[default]
+    Detect,
+    /// Always use synchronized output.
+    Enabled,
+    /// Never use synchronized output.
+    Disabled,
+}
+
+/// Whether a [`Terminal`] draws the extended kinds of underline (double, curly, dotted, and dashed)
+/// and underline colors.
+///
+/// Terminals that don't support them either ignore the sequences or misinterpret them, so when
+/// they're disabled, every kind of underline is drawn as a plain underline in the text's color.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ExtendedUnderlines {
+    /// Guess whether the terminal supports extended underlines from its environment variables.
+    #[default]
+    Detect,
+    /// Always use extended underlines.
+    Enabled,
+    /// Never use extended underlines.
+    Disabled,
+}
+
 impl super::Platform for Terminal {
     fn run(mut self, mut program: impl Program) -> Result<(), Box<dyn std::error::Error>> {
-        bind_terminal()?;
-
-        'main_loop: loop {
-            if crossterm::event::poll(std::time::Duration::from_millis(31))? {
-                match crossterm::event::read()? {
-                    crossterm::event::Event::Key(KeyEvent { code, modifiers, kind, .. }) => {
-                        let mut scancodes = vec![];
-                        if modifiers != KeyModifiers::NONE {
-                            for m in modifiers.iter() {
-                                match m {
-                                    KeyModifiers::SHIFT => scancodes.push(Scancode::L_SHIFT),
-                                    KeyModifiers::ALT => scancodes.push(Scancode::L_ALT),
-                                    KeyModifiers::CONTROL => scancodes.push(Scancode::L_CTRL),
-                                    _ => {} // TODO: Handle other modifiers.
-                                }
-                            }
-                        }
-                        scancodes.extend(keycode_to_scancode(code));
-                        match kind {
-                            KeyEventKind::Press | KeyEventKind::Repeat => {
-                                for scancode in scancodes {
-                                    program.input(Input::KeyDown(scancode));
-                                }
-                            }
-                            KeyEventKind::Release => {
-                                for scancode in scancodes {
-                                    program.input(Input::KeyUp(scancode));
-                                }
-                            }
-                        }
-                    }
-                    crossterm::event::Event::Mouse(MouseEvent { kind, column, row, .. }) => {
-                        match kind {
-                            MouseEventKind::Moved | MouseEventKind::Drag(_) => {
-                                program.input(Input::MouseMove(column, row));
-                            }
-                            MouseEventKind::Down(btn) => {
-                                let code = match btn {
-                                    crossterm::event::MouseButton::Left => MouseButton::Left,
-                                    crossterm::event::MouseButton::Right => MouseButton::Right,
-                                    crossterm::event::MouseButton::Middle => MouseButton::Middle,
-                                };
-                                program.input(Input::MouseDown(code));
-                            }
-                            MouseEventKind::Up(btn) => {
-                                let code = match btn {
-                                    crossterm::event::MouseButton::Left => MouseButton::Left,
-                                    crossterm::event::MouseButton::Right => MouseButton::Right,
-                                    crossterm::event::MouseButton::Middle => MouseButton::Middle,
-                                };
-                                program.input(Input::MouseUp(code));
-                            }
-                            MouseEventKind::ScrollUp => {
-                                program.input(Input::WheelUp);
-                            }
-                            MouseEventKind::ScrollDown => {
-                                program.input(Input::WheelDown);
-                            }
-                            _ => {} // TODO: ScrollRight and ScrollLeft handling.
-                        }
-                    }
-                    crossterm::event::Event::FocusGained => {
-                        program.input(Input::FocusChange(true));
-                    }
-                    crossterm::event::Event::FocusLost => {
-                        program.input(Input::FocusChange(false));
-                    }
-                    crossterm::event::Event::Resize(new_cols, new_rows) => {
-                        program.input(Input::Resize(new_cols, new_rows));
-                    }
-                    _ => {}
+        let synchronized = match bind_terminal(self.synchronized_output) {
+            Ok(synchronized) => synchronized,
+            Err(error) => {
+                let _ = release_terminal();
+                return Err(error);
+            }
+        };
+        let extended_underlines = match self.extended_underlines {
+            ExtendedUnderlines::Detect => detect_extended_underlines(),
+            ExtendedUnderlines::Enabled => true,
+            ExtendedUnderlines::Disabled => false,
+        };
+
+        let stop_input = Arc::new(AtomicBool::new(false));
+        let input_thread = spawn_input_thread(self.waker.clone(), stop_input.clone());
+
+        // However the loop ends, the input thread has to be stopped and the terminal released
+        // before returning, so that an error doesn't leave the terminal in raw mode.
+        let result = self.run_loop(&mut program, synchronized, extended_underlines);
+
+        stop_input.store(true, Ordering::Relaxed);
+        let _ = input_thread.join();
+        let released = release_terminal();
+        result.and(released)
+    }
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Terminal {
+    pub fn new() -> Self {
+        let (waker, signals) = waker::channel();
+        Self {
+            waker,
+            signals,
+            buffers: [Buffer::empty(), Buffer::empty()],
+            current: 0,
+            layers: Layers::default(),
+            theme: Theme::default(),
+            damage: Damage::full(),
+            retained: Buffer::empty(),
+            layered: false,
+            clear_color: None,
+            updates: Vec::new(),
+            output: Vec::new(),
+            synchronized_output: SynchronizedOutput::default(),
+            extended_underlines: ExtendedUnderlines::default(),
+            last_known_size: (0, 0),
+        }
+    }
+
+    /// Get a [`Waker`] for sending messages to the program from other threads once the terminal
+    /// is running.
+    pub fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+
+    /// Set the [`Theme`] given to the program with each frame.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Set whether each frame should be drawn as a single synchronized update.
+    pub fn with_synchronized_output(mut self, synchronized_output: SynchronizedOutput) -> Self {
+        self.synchronized_output = synchronized_output;
+        self
+    }
+
+    /// Set whether extended underline styles and underline colors should be drawn.
+    pub fn with_extended_underlines(mut self, extended_underlines: ExtendedUnderlines) -> Self {
+        self.extended_underlines = extended_underlines;
+        self
+    }
+
+    /// Run the program until it exits or something fails, rendering frames and handling input.
+    fn run_loop(
+        &mut self,
+        program: &mut impl Program,
+        synchronized: bool,
+        extended_underlines: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut needs_render = true;
+        let mut next_redraw: Option<Instant> = None;
+
+        loop {
+            let on_demand = program.render_mode() == RenderMode::OnDemand;
+            let timeout = if !on_demand {
+                FRAME_INTERVAL
+            } else if needs_render {
+                Duration::ZERO
+            } else {
+                next_redraw.map_or(IDLE_INTERVAL, |at| at.saturating_duration_since(Instant::now()))
+            };
+            let mut signal = self.signals.recv_timeout(timeout).ok();
+            while let Some(next) = signal {
+                needs_render = true;
+                match next {
+                    Signal::Input(input) => program.input(input),
+                    Signal::Message(message) => program.message(message),
+                    Signal::Redraw => {}
+                    Signal::Error(error) => return Err(error.into()),
                 }
+                signal = self.signals.try_recv().ok();
             }
             // TODO: Optimize this by storing terminal size?
             let (cols, rows) = crossterm::terminal::size()?;
             if (cols, rows) != self.last_known_size {
                 let area = Area::new(0, 0, cols, rows);
-                self.buffers[self.current].resize(area);
-                self.buffers[1 - self.current].resize(area);
+                for buffer in &mut self.buffers {
+                    buffer.resize(area);
+                    buffer.reset();
+                }
+                self.retained.resize(area);
+                self.retained.reset();
+                self.damage.add_all();
+                // Whatever was on the screen before the resize is no longer reliable.
+                self.clear_color = None;
                 self.last_known_size = (cols, rows);
+                // A full redraw needs roughly this much room.
+                self.output.reserve(cols as usize * rows as usize * 4);
+                needs_render = true;
+            }
+            if next_redraw.is_some_and(|at| at <= Instant::now()) {
+                needs_render = true;
+            }
+            if on_demand && !needs_render {
+                continue;
+            }
+            needs_render = false;
+
+            let time = Instant::now();
+            program.update();
+            let retained = program.retained_rendering();
+
+            if retained {
+                self.restore_retained();
+            } else {
+                self.damage.add_all();
             }
+            self.layers.begin(Area::new(0, 0, cols, rows));
 
             let mut commands = Vec::with_capacity(1);
-            let mut frame = Frame {
-                cols,
-                rows,
-                buffer: &mut self.buffers[self.current],
-                commands: &mut commands,
-                cursor: None,
-                should_exit: false,
-            };
+            let mut frame = Frame::new(
+                &mut self.buffers[self.current],
+                &mut self.damage,
+                &mut self.layers,
+                &mut self.theme,
+                &mut commands,
+                time,
+            );
 
             program.render(&mut frame);
 
             let next_cursor = frame.cursor;
+            next_redraw = frame.next_redraw;
 
             if frame.should_exit {
-                break 'main_loop;
+                return Ok(());
             }
 
-            self.flush()?;
-            self.swap_buffers();
+            let layered = self.layers.any_active();
+            if retained && layered {
+                self.retained.clone_from(&self.buffers[self.current]);
+            }
+            self.layers.draw(&mut self.buffers[self.current]);
+            if layered || self.layered {
+                // Layers can change anywhere, regardless of what the program marked as dirty.
+                self.damage.add_all();
+            }
+            self.layered = layered;
+
+            self.output.clear();
+            if synchronized {
+                queue!(self.output, crossterm::terminal::BeginSynchronizedUpdate)?;
+            }
+            self.draw(program.clear_color(), extended_underlines)?;
+            self.swap_buffers(retained);
+            self.damage.clear();
 
-            let mut writer = std::io::stdout();
+            let output = &mut self.output;
 
             match next_cursor {
                 None => {
-                    queue!(writer, crossterm::cursor::Hide)?;
+                    queue!(output, crossterm::cursor::Hide)?;
                 }
                 Some((x, y)) => {
-                    queue!(writer, crossterm::cursor::Show)?;
-                    queue!(writer, crossterm::cursor::MoveTo(x, y))?;
+                    queue!(output, crossterm::cursor::Show)?;
+                    queue!(output, crossterm::cursor::MoveTo(x, y))?;
                 }
             }
 
             for command in commands {
                 match command {
-                    Command::SetTitle(s) => queue!(writer, crossterm::terminal::SetTitle(s)),
-                    Command::SetCursorStyle(cursor_style) => queue!(writer, match cursor_style {
+                    Command::SetTitle(s) => queue!(output, crossterm::terminal::SetTitle(s)),
+                    Command::SetCursorStyle(cursor_style) => queue!(output, match cursor_style {
                         CursorStyle::SteadyBar =>
                             crossterm::cursor::SetCursorStyle::SteadyBar,
                         CursorStyle::SteadyBlock =>
@@ -168,84 +327,439 @@ impl super::Platform for Terminal {
                 }?
             }
 
+            if synchronized {
+                queue!(output, crossterm::terminal::EndSynchronizedUpdate)?;
+            }
+
+            let mut writer = std::io::stdout().lock();
+            writer.write_all(output)?;
             writer.flush()?;
         }
+    }
 
-        release_terminal()?;
+    /// Swap the inactive buffer with the current buffer, clearing it unless it's about to be
+    /// overwritten with the retained contents.
+    fn swap_buffers(&mut self, retained: bool) {
+        if !retained {
+            self.buffers[1 - self.current].reset();
+        }
+        self.current = 1 - self.current;
+    }
 
-        Ok(())
+    /// Fill the current buffer with the contents of the last render pass, from before any layers
+    /// were drawn onto it.
+    fn restore_retained(&mut self) {
+        let [first, second] = &mut self.buffers;
+        let (previous_buffer, current_buffer) = if self.current == 0 {
+            (&*second, first)
+        } else {
+            (&*first, second)
+        };
+        if self.layered {
+            current_buffer.clone_from(&self.retained);
+        } else {
+            current_buffer.clone_from(previous_buffer);
+        }
+    }
+
+    /// Write the changes between the previous buffer and the current one to the output. Only the
+    /// damaged rows are compared.
+    ///
+    /// If part of the screen was shifted up or down, the terminal is told to scroll that part
+    /// first, so that only the rows that were scrolled into view need to be written.
+    ///
+    /// The whole screen is cleared first if the clear color has changed since the last frame.
+    fn draw(&mut self, clear_color: Color, extended_underlines: bool) -> std::io::Result<()> {
+        let [first, second] = &mut self.buffers;
+        let (previous_buffer, current_buffer) = if self.current == 0 {
+            (second, &*first)
+        } else {
+            (first, &*second)
+        };
+        let background = SetBackgroundColor(color_to_crossterm_color(clear_color));
+        let reset = SetBackgroundColor(CtColor::Reset);
+
+        if self.clear_color != Some(clear_color) {
+            self.clear_color = Some(clear_color);
+            let clear = crossterm::terminal::Clear(crossterm::terminal::ClearType::All);
+            queue!(self.output, background, clear, reset)?;
+            previous_buffer.reset();
+            previous_buffer.diff_into(current_buffer, &mut self.updates);
+        } else if self.damage.is_full() {
+            if let Some(scroll) = Scroll::detect(previous_buffer, current_buffer) {
+                // Rows scrolled into view are filled with the current background color.
+                queue!(self.output, background)?;
+                scroll.write(&mut self.output);
+                queue!(self.output, reset)?;
+                scroll.apply(previous_buffer);
+            }
+            previous_buffer.diff_into(current_buffer, &mut self.updates);
+        } else {
+            let rows = self.damage.rows(current_buffer.area);
+            previous_buffer.diff_rows_into(current_buffer, &rows, &mut self.updates);
+        }
+
+        write_updates(
+            current_buffer,
+            &self.updates,
+            clear_color,
+            extended_underlines,
+            &mut self.output,
+        )
     }
 }
 
-impl Terminal {
-    pub fn new() -> Self {
-        Self {
-            buffers: [Buffer::empty(), Buffer::empty()],
-            current: 0,
-            last_known_size: (0, 0),
+
+
+/// A vertical shift of the rows in part of the screen.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Scroll {
+    /// The first row of the scrolled region.
+    top: u16,
+    /// The row after the last row of the scrolled region.
+    bottom: u16,
+    /// The number of rows the region's content moves by.
+    amount: u16,
+    /// Whether the content moves up (rather than down).
+    up: bool,
+}
+
+impl Scroll {
+    /// Find the shift that lets the most rows of the current buffer be reused from the previous
+    /// one, if it's worth scrolling for.
+    fn detect(previous: &Buffer, current: &Buffer) -> Option<Self> {
+        use std::hash::{DefaultHasher, Hash as _, Hasher as _};
+
+        /// The fewest rows a scroll needs to save for it to be used.
+        const MIN_SAVED_ROWS: usize = 2;
+
+        if previous.area != current.area || previous.area.h < 3 || previous.area.w == 0 {
+            return None;
         }
+        let w = current.area.w as usize;
+        let h = current.area.h as usize;
+        let hash_rows = |buf: &Buffer| -> Vec<u64> {
+            buf.content.chunks(w)
+                .map(|row| {
+                    let mut hasher = DefaultHasher::new();
+                    row.hash(&mut hasher);
+                    hasher.finish()
+                })
+                .collect()
+        };
+        let previous_rows = hash_rows(previous);
+        let current_rows = hash_rows(current);
+
+        let mut best: Option<(usize, Self)> = None;
+        for amount in 1..h / 2 + 1 {
+            for up in [true, false] {
+                // Look for runs of rows that match a row `amount` rows below (when scrolling up)
+                // or above (when scrolling down) in the previous buffer.
+                let mut run_start = 0;
+                let mut saved = 0;
+                for r in 0..=h - amount {
+                    let matches = r < h - amount && {
+                        let (current_row, previous_row) = if up {
+                            (r, r + amount)
+                        } else {
+                            (r + amount, r)
+                        };
+                        current_rows[current_row] == previous_rows[previous_row]
+                    };
+                    if matches {
+                        let current_row = if up { r } else { r + amount };
+                        // Rows that didn't change don't need to be scrolled.
+                        if current_rows[current_row] != previous_rows[current_row] {
+                            saved += 1;
+                        }
+                        continue;
+                    }
+                    if saved >= MIN_SAVED_ROWS && best.is_none_or(|(most, _)| saved > most) {
+                        best = Some((saved, Self {
+                            top: run_start as u16,
+                            bottom: (r + amount) as u16,
+                            amount: amount as u16,
+                            up,
+                        }));
+                    }
+                    run_start = r + 1;
+                    saved = 0;
+                }
+            }
+        }
+
+        best.map(|(_, scroll)| scroll)
     }
 
-    /// Clear the inactive buffer and swap it with the current buffer.
-    fn swap_buffers(&mut self) {
-        self.buffers[1 - self.current].reset();
-        self.current = 1 - self.current;
+    /// Write the sequences that perform this scroll (and reset the scrolling region afterwards).
+    fn write(&self, out: &mut Vec<u8>) {
+        use std::io::Write as _;
+
+        let _ = write!(
+            out,
+            "\x1b[{};{}r\x1b[{}{}\x1b[r",
+            self.top + 1,
+            self.bottom,
+            self.amount,
+            if self.up { 'S' } else { 'T' },
+        );
     }
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        let previous_buffer = &self.buffers[1 - self.current];
-        let current_buffer = &self.buffers[self.current];
-        let updates = previous_buffer.diff(current_buffer);
-        let content = updates.into_iter();
-
-        let mut writer = std::io::stdout();
-        let mut fg = Color::Reset;
-        let mut bg = Color::Reset;
-        let mut modifier = Modifier::empty();
-        let mut last_pos: Option<(u16, u16)> = None;
-        for (x, y, cell) in content {
-            // Move the cursor if the previous location was not (x - 1, y).
-            if !matches!(last_pos, Some(p) if x == p.0 + 1 && y == p.1) {
-                queue!(writer, crossterm::cursor::MoveTo(x, y))?;
-            }
-            last_pos = Some((x, y));
-            if cell.modifier != modifier {
-                let diff = ModifierDiff {
-                    from: modifier,
-                    to: cell.modifier,
-                };
-                diff.queue(&mut writer)?;
-                modifier = cell.modifier;
+    /// Shift the rows of the buffer the same way the terminal's screen is shifted by this
+    /// scroll, leaving empty rows where new content was scrolled in.
+    fn apply(&self, buffer: &mut Buffer) {
+        let w = buffer.area.w as usize;
+        let region = &mut buffer.content[self.top as usize * w..self.bottom as usize * w];
+        let shifted = self.amount as usize * w;
+        let len = region.len();
+        if self.up {
+            region.rotate_left(shifted);
+            region[len - shifted..].fill(Cell::EMPTY);
+        } else {
+            region.rotate_right(shifted);
+            region[..shifted].fill(Cell::EMPTY);
+        }
+    }
+}
+
+
+
+/// Write the cells at the given indices of the buffer to the output.
+///
+/// Adjacent cells that share a style are written as a single run, and the cursor is moved
+/// between runs with whatever sequence takes the fewest bytes.
+///
+/// Cells with a [`Color::Reset`] background are drawn with the given clear color instead.
+///
+/// Without extended underlines, every kind of underline is written as a plain underline, and
+/// underline colors are left out.
+///
+/// Runs of cells with the same hyperlink are wrapped in OSC 8 sequences.
+fn write_updates(
+    buffer: &Buffer,
+    updates: &[usize],
+    clear_color: Color,
+    extended_underlines: bool,
+    out: &mut Vec<u8>,
+) -> std::io::Result<()> {
+    let background = |cell: &Cell| if cell.bg == Color::Reset { clear_color } else { cell.bg };
+    let underline_color = |cell: &Cell| {
+        if extended_underlines { cell.underline_color } else { Color::Reset }
+    };
+    let mut fg = Color::Reset;
+    let mut bg = Color::Reset;
+    let mut ul = Color::Reset;
+    let mut hyperlink: Option<&str> = None;
+    let mut modifier = Modifier::empty();
+    // The terminal's cursor position, if it's known.
+    let mut cursor: Option<(u16, u16)> = None;
+
+    let mut i = 0;
+    while i < updates.len() {
+        let cell = &buffer.content[updates[i]];
+        let (x, y) = buffer.pos_of(updates[i]);
+        move_cursor(out, cursor, (x, y));
+
+        if cell.modifier != modifier {
+            let diff = ModifierDiff {
+                from: modifier,
+                to: cell.modifier,
+                extended_underlines,
+            };
+            diff.queue(&mut *out)?;
+            modifier = cell.modifier;
+        }
+        if cell.fg != fg {
+            queue!(out, crossterm::style::SetForegroundColor(color_to_crossterm_color(cell.fg)))?;
+            fg = cell.fg;
+        }
+        if background(cell) != bg {
+            bg = background(cell);
+            queue!(out, crossterm::style::SetBackgroundColor(color_to_crossterm_color(bg)))?;
+        }
+        if underline_color(cell) != ul {
+            ul = underline_color(cell);
+            queue!(out, SetUnderlineColor(color_to_crossterm_color(ul)))?;
+        }
+        if cell.hyperlink() != hyperlink {
+            hyperlink = cell.hyperlink();
+            write_hyperlink(out, hyperlink);
+        }
+
+        // Write this cell, along with every following cell that continues the run.
+        let mut end_x = x;
+        loop {
+            let symbol = buffer.content[updates[i]].symbol();
+            if symbol.is_empty() {
+                out.push(b' ');
+                end_x += 1;
+            } else {
+                out.extend_from_slice(symbol.as_bytes());
+                end_x += symbol.width().max(1) as u16;
             }
-            if cell.fg != fg || cell.bg != bg {
-                queue!(
-                    writer,
-                    crossterm::style::SetColors(crossterm::style::Colors::new(
-                        color_to_crossterm_color(cell.fg),
-                        color_to_crossterm_color(cell.bg),
-                    ))
-                )?;
-                fg = cell.fg;
-                bg = cell.bg;
+            i += 1;
+
+            let Some(&next) = updates.get(i) else {
+                break;
+            };
+            let next_cell = &buffer.content[next];
+            if buffer.pos_of(next) != (end_x, y)
+                || next_cell.fg != fg
+                || background(next_cell) != bg
+                || underline_color(next_cell) != ul
+                || next_cell.hyperlink() != hyperlink
+                || next_cell.modifier != modifier
+            {
+                break;
             }
+        }
+        // After writing to the last column, terminals disagree about where the cursor is.
+        cursor = (end_x < buffer.area.right()).then_some((end_x, y));
+    }
 
-            queue!(writer, crossterm::style::Print(cell.symbol()))?;
+    if hyperlink.is_some() {
+        write_hyperlink(out, None);
+    }
+    crossterm::queue!(
+        out,
+        crossterm::style::SetForegroundColor(CtColor::Reset),
+        crossterm::style::SetBackgroundColor(CtColor::Reset),
+        crossterm::style::SetAttribute(Attribute::Reset),
+    )
+}
+
+/// Start a hyperlink to the given target, or end the current one with `None`.
+///
+/// Control characters are left out of the target, since they could end the sequence early.
+fn write_hyperlink(out: &mut Vec<u8>, target: Option<&str>) {
+    out.extend_from_slice(b"\x1b]8;;");
+    for ch in target.unwrap_or_default().chars().filter(|ch| !ch.is_control()) {
+        let mut buf = [0; 4];
+        out.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+    }
+    out.extend_from_slice(b"\x1b\\");
+}
+
+/// Move the cursor from `from` (if the cursor's position is known) to `to`, using the sequence
+/// with the fewest bytes.
+fn move_cursor(out: &mut Vec<u8>, from: Option<(u16, u16)>, to: (u16, u16)) {
+    use std::io::Write as _;
+
+    let (x, y) = to;
+    let absolute_cost = 4 + digits(y + 1) + digits(x + 1);
+    let Some((cx, cy)) = from else {
+        let _ = write!(out, "\x1b[{};{}H", y + 1, x + 1);
+        return;
+    };
+    if (cx, cy) == to {
+        return;
+    }
+
+    // Moving to a row below can be done with line feeds, which don't change the column on Unix
+    // (output post-processing is disabled in raw mode). Windows consoles can return to the first
+    // column on a line feed as well, so they always move down with `CSI n B`.
+    let vertical = if y == cy {
+        (0, Vertical::None)
+    } else if y > cy {
+        let n = y - cy;
+        if cfg!(unix) && n as usize <= 3 + digits_n(n) {
+            (n as usize, Vertical::LineFeeds(n))
+        } else {
+            (3 + digits_n(n), Vertical::Down(n))
         }
+    } else {
+        (3 + digits_n(cy - y), Vertical::Up(cy - y))
+    };
+    let horizontal = [
+        (x == cx).then_some((0, Horizontal::None)),
+        (x == 0).then_some((1, Horizontal::CarriageReturn)),
+        (x > cx).then(|| (3 + digits_n(x - cx), Horizontal::Forward(x - cx))),
+        (x < cx).then(|| (3 + digits_n(cx - x), Horizontal::Back(cx - x))),
+        (x > 0).then(|| (4 + digits_n(x), Horizontal::ReturnForward(x))),
+        Some((3 + digits(x + 1), Horizontal::Column(x))),
+    ]
+    .into_iter()
+    .flatten()
+    .min_by_key(|(cost, _)| *cost)
+    .unwrap_or((usize::MAX, Horizontal::None));
 
-        crossterm::queue!(
-            writer,
-            crossterm::style::SetForegroundColor(CtColor::Reset),
-            crossterm::style::SetBackgroundColor(CtColor::Reset),
-            crossterm::style::SetAttribute(Attribute::Reset),
-        )
+    if vertical.0 + horizontal.0 >= absolute_cost {
+        let _ = write!(out, "\x1b[{};{}H", y + 1, x + 1);
+        return;
+    }
+    match vertical.1 {
+        Vertical::None => {}
+        Vertical::LineFeeds(n) => out.extend(std::iter::repeat_n(b'\n', n as usize)),
+        Vertical::Down(n) => write_csi(out, n, b'B'),
+        Vertical::Up(n) => write_csi(out, n, b'A'),
+    }
+    match horizontal.1 {
+        Horizontal::None => {}
+        Horizontal::CarriageReturn => out.push(b'\r'),
+        Horizontal::Forward(n) => write_csi(out, n, b'C'),
+        Horizontal::Back(n) => write_csi(out, n, b'D'),
+        Horizontal::ReturnForward(n) => {
+            out.push(b'\r');
+            write_csi(out, n, b'C');
+        }
+        Horizontal::Column(x) => write_csi(out, x + 1, b'G'),
     }
 }
 
+enum Vertical {
+    None,
+    LineFeeds(u16),
+    Down(u16),
+    Up(u16),
+}
 
+enum Horizontal {
+    None,
+    CarriageReturn,
+    Forward(u16),
+    Back(u16),
+    ReturnForward(u16),
+    Column(u16),
+}
 
-fn bind_terminal() -> Result<(), Box<dyn std::error::Error>> {
+/// Write a control sequence with a single numeric parameter, leaving it out when it's 1 (which
+/// is the default for all of the sequences used here).
+fn write_csi(out: &mut Vec<u8>, n: u16, op: u8) {
+    use std::io::Write as _;
+
+    if n == 1 {
+        out.extend_from_slice(&[0x1b, b'[', op]);
+    } else {
+        let _ = write!(out, "\x1b[{}{}", n, op as char);
+    }
+}
+
+/// The number of decimal digits in `n`.
+fn digits(n: u16) -> usize {
+    n.checked_ilog10().unwrap_or(0) as usize + 1
+}
+
+/// The number of decimal digits needed for `n` as a [`write_csi`] parameter.
+fn digits_n(n: u16) -> usize {
+    if n == 1 { 0 } else { digits(n) }
+}
+
+
+
+/// Set up the terminal for rendering and input, and return whether to use synchronized output.
+///
+/// Synchronized output is detected after entering raw mode, but before mouse and keyboard
+/// reporting are turned on, so that the probe can't swallow their events.
+fn bind_terminal(
+    synchronized_output: SynchronizedOutput,
+) -> Result<bool, Box<dyn std::error::Error>> {
     let mut writer = std::io::stdout();
     crossterm::terminal::enable_raw_mode()?;
+    let synchronized = match synchronized_output {
+        SynchronizedOutput::Detect => detect_synchronized_output(),
+        SynchronizedOutput::Enabled => true,
+        SynchronizedOutput::Disabled => false,
+    };
     writer.execute(crossterm::event::EnableMouseCapture)?;
     writer.execute(crossterm::event::EnableFocusChange)?;
     writer.execute(crossterm::terminal::EnterAlternateScreen)?;
@@ -260,7 +774,7 @@ fn bind_terminal() -> Result<(), Box<dyn std::error::Error>> {
         original_hook(panic);
     }));
 
-    Ok(())
+    Ok(synchronized)
 }
 
 fn release_terminal() -> Result<(), Box<dyn std::error::Error>> {
@@ -275,6 +789,204 @@ fn release_terminal() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Ask the terminal whether it supports synchronized output.
+///
+/// This must be called while in raw mode, before mouse and keyboard reporting are turned on, and
+/// before any events are read, since it reads the terminal's input directly.
+#[cfg(unix)]
+fn detect_synchronized_output() -> bool {
+    use std::{io::Read as _, os::fd::AsRawFd as _, time::{Duration, Instant}};
+
+    // Request the state of mode 2026, followed by the primary device attributes. Every terminal
+    // answers the second request, so once its response arrives there's no point in waiting for
+    // an answer to the first.
+    const QUERY: &[u8] = b"\x1b[?2026$p\x1b[c";
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    let Ok(mut tty) = std::fs::File::options().read(true).write(true).open("/dev/tty") else {
+        return false;
+    };
+    if tty.write_all(QUERY).and_then(|_| tty.flush()).is_err() {
+        return false;
+    }
+
+    let deadline = Instant::now() + TIMEOUT;
+    let mut response = Vec::new();
+    let mut chunk = [0; 64];
+    // Keep reading until the device attributes arrive, even once the mode has been reported, so
+    // that the rest of the response isn't left behind to be read as input.
+    while !contains_device_attributes(&response) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let mut poll_fd = libc::pollfd { fd: tty.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        // SAFETY: `poll_fd` is a valid `pollfd`, and the count of 1 matches it.
+        let ready = unsafe { libc::poll(&mut poll_fd, 1, remaining.as_millis() as libc::c_int) };
+        if ready <= 0 {
+            break;
+        }
+        match tty.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => response.extend_from_slice(&chunk[..n]),
+        }
+    }
+
+    // 1 and 2 mean the mode is set or reset, and 3 means it's permanently set.
+    parse_mode_report(&response, 2026).is_some_and(|state| matches!(state, 1..=3))
+}
+
+#[cfg(not(unix))]
+fn detect_synchronized_output() -> bool {
+    false
+}
+
+/// Guess whether the terminal supports extended underlines from its environment variables.
+///
+/// Terminals don't offer a reliable way to ask about this, so only the ones that are known to
+/// support them are detected.
+fn detect_extended_underlines() -> bool {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    supports_extended_underlines(
+        &var("TERM"),
+        &var("TERM_PROGRAM"),
+        var("VTE_VERSION").parse().ok(),
+    )
+}
+
+fn supports_extended_underlines(term: &str, term_program: &str, vte_version: Option<u32>) -> bool {
+    // Multiplexers only pass the sequences through if they've been configured to.
+    if term.starts_with("screen") || term.starts_with("tmux") {
+        return false;
+    }
+    // VTE has supported them since 0.51.2.
+    if vte_version.is_some_and(|version| version >= 5102) {
+        return true;
+    }
+    matches!(term_program, "WezTerm" | "iTerm.app" | "ghostty")
+        || ["kitty", "wezterm", "foot", "alacritty", "ghostty", "contour"]
+            .iter()
+            .any(|name| term.contains(name))
+}
+
+/// Find the state reported for the given DEC private mode in a DECRPM response
+/// (`CSI ? mode ; state $ y`).
+fn parse_mode_report(bytes: &[u8], mode: u16) -> Option<u8> {
+    let prefix = format!("\x1b[?{mode};");
+    let start = bytes.windows(prefix.len()).position(|w| w == prefix.as_bytes())? + prefix.len();
+    let rest = &bytes[start..];
+    let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+    if !rest[digits..].starts_with(b"$y") {
+        return None;
+    }
+    std::str::from_utf8(&rest[..digits]).ok()?.parse().ok()
+}
+
+/// Whether the bytes contain a primary device attributes response (`CSI ? attributes c`).
+fn contains_device_attributes(bytes: &[u8]) -> bool {
+    bytes.windows(3).enumerate().any(|(i, w)| {
+        w == b"\x1b[?" && bytes[i + 3..]
+            .iter()
+            .find(|b| !b.is_ascii_digit() && **b != b';')
+            .is_some_and(|b| *b == b'c')
+    })
+}
+
+/// Read input events on a separate thread, so that the event loop can wait for input and messages
+/// from [`Waker`]s at the same time.
+fn spawn_input_thread(waker: Waker, stop: Arc<AtomicBool>) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        while !stop.load(Ordering::Relaxed) {
+            let event = match crossterm::event::poll(INPUT_INTERVAL) {
+                Ok(false) => continue,
+                Ok(true) => crossterm::event::read(),
+                Err(error) => Err(error),
+            };
+            let signals = match event {
+                Ok(event) => event_to_inputs(event).into_iter().map(Signal::Input).collect(),
+                Err(error) => vec![Signal::Error(error)],
+            };
+            for signal in signals {
+                let stopping = matches!(signal, Signal::Error(_));
+                if !waker.signal(signal) || stopping {
+                    return;
+                }
+            }
+        }
+    })
+}
+
+/// Convert a crossterm event into the [`Input`]s it represents.
+fn event_to_inputs(event: crossterm::event::Event) -> Vec<Input> {
+    let mut inputs = Vec::with_capacity(1);
+    match event {
+        crossterm::event::Event::Key(KeyEvent { code, modifiers, kind, .. }) => {
+            let mut scancodes = vec![];
+            if modifiers != KeyModifiers::NONE {
+                for m in modifiers.iter() {
+                    match m {
+                        KeyModifiers::SHIFT => scancodes.push(Scancode::L_SHIFT),
+                        KeyModifiers::ALT => scancodes.push(Scancode::L_ALT),
+                        KeyModifiers::CONTROL => scancodes.push(Scancode::L_CTRL),
+                        _ => {} // TODO: Handle other modifiers.
+                    }
+                }
+            }
+            scancodes.extend(keycode_to_scancode(code));
+            match kind {
+                KeyEventKind::Press | KeyEventKind::Repeat => {
+                    for scancode in scancodes {
+                        inputs.push(Input::KeyDown(scancode));
+                    }
+                }
+                KeyEventKind::Release => {
+                    for scancode in scancodes {
+                        inputs.push(Input::KeyUp(scancode));
+                    }
+                }
+            }
+        }
+        crossterm::event::Event::Mouse(MouseEvent { kind, column, row, .. }) => {
+            match kind {
+                MouseEventKind::Moved | MouseEventKind::Drag(_) => {
+                    inputs.push(Input::MouseMove(column, row));
+                }
+                MouseEventKind::Down(btn) => {
+                    let code = match btn {
+                        crossterm::event::MouseButton::Left => MouseButton::Left,
+                        crossterm::event::MouseButton::Right => MouseButton::Right,
+                        crossterm::event::MouseButton::Middle => MouseButton::Middle,
+                    };
+                    inputs.push(Input::MouseDown(code));
+                }
+                MouseEventKind::Up(btn) => {
+                    let code = match btn {
+                        crossterm::event::MouseButton::Left => MouseButton::Left,
+                        crossterm::event::MouseButton::Right => MouseButton::Right,
+                        crossterm::event::MouseButton::Middle => MouseButton::Middle,
+                    };
+                    inputs.push(Input::MouseUp(code));
+                }
+                MouseEventKind::ScrollUp => {
+                    inputs.push(Input::WheelUp);
+                }
+                MouseEventKind::ScrollDown => {
+                    inputs.push(Input::WheelDown);
+                }
+                _ => {} // TODO: ScrollRight and ScrollLeft handling.
+            }
+        }
+        crossterm::event::Event::FocusGained => {
+            inputs.push(Input::FocusChange(true));
+        }
+        crossterm::event::Event::FocusLost => {
+            inputs.push(Input::FocusChange(false));
+        }
+        crossterm::event::Event::Resize(new_cols, new_rows) => {
+            inputs.push(Input::Resize(new_cols, new_rows));
+        }
+        _ => {}
+    }
+    inputs
+}
+
 fn keycode_to_scancode(code: KeyCode) -> Vec<Scancode> {
     // All of `crossterm`'s keycodes translate to 2 or less scancodes.
     let mut scancodes = Vec::with_capacity(2);
@@ -343,7 +1055,23 @@ fn color_to_crossterm_color(color: Color) -> CtColor {
         Color::Reset => CtColor::Reset,
         Color::Ansi(i) => CtColor::AnsiValue(i),
         Color::Rgb(r, g, b) => CtColor::Rgb { r, g, b },
-        _ => unreachable!("tried to translate invalid color tag"),
+        // Crossterm calls the normal ANSI colors "dark", and the bright ones "normal".
+        Color::Black => CtColor::Black,
+        Color::Red => CtColor::DarkRed,
+        Color::Green => CtColor::DarkGreen,
+        Color::Yellow => CtColor::DarkYellow,
+        Color::Blue => CtColor::DarkBlue,
+        Color::Magenta => CtColor::DarkMagenta,
+        Color::Cyan => CtColor::DarkCyan,
+        Color::Gray => CtColor::Grey,
+        Color::DarkGray => CtColor::DarkGrey,
+        Color::LightRed => CtColor::Red,
+        Color::LightGreen => CtColor::Green,
+        Color::LightYellow => CtColor::Yellow,
+        Color::LightBlue => CtColor::Blue,
+        Color::LightMagenta => CtColor::Magenta,
+        Color::LightCyan => CtColor::Cyan,
+        Color::White => CtColor::White,
     }
 }
 
@@ -353,9 +1081,29 @@ fn color_to_crossterm_color(color: Color) -> CtColor {
 struct ModifierDiff {
     pub from: Modifier,
     pub to: Modifier,
+    /// Whether the extended kinds of underline can be written, rather than plain underlines.
+    pub extended_underlines: bool,
 }
 
 impl ModifierDiff {
+    /// The attribute for the kind of underline that the given modifier is drawn with.
+    fn underline(&self, modifier: Modifier) -> Option<Attribute> {
+        let underlines = modifier & Modifier::UNDERLINES;
+        if underlines.is_empty() {
+            None
+        } else if !self.extended_underlines || underlines.contains(Modifier::UNDERLINED) {
+            Some(Attribute::Underlined)
+        } else if underlines.contains(Modifier::DOUBLE_UNDERLINED) {
+            Some(Attribute::DoubleUnderlined)
+        } else if underlines.contains(Modifier::CURLY_UNDERLINED) {
+            Some(Attribute::Undercurled)
+        } else if underlines.contains(Modifier::DOTTED_UNDERLINED) {
+            Some(Attribute::Underdotted)
+        } else {
+            Some(Attribute::Underdashed)
+        }
+    }
+
     fn queue<W: std::io::Write>(self, mut w: W) -> std::io::Result<()> {
         //use crossterm::Attribute;
         let removed = self.from - self.to;
@@ -371,8 +1119,11 @@ impl ModifierDiff {
         if removed.contains(Modifier::ITALIC) {
             queue!(w, SetAttribute(Attribute::NoItalic))?;
         }
-        if removed.contains(Modifier::UNDERLINED) {
-            queue!(w, SetAttribute(Attribute::NoUnderline))?;
+        // Each kind of underline replaces the previous one, so they only need to be written when
+        // the kind changes.
+        let underline = self.underline(self.to);
+        if underline != self.underline(self.from) {
+            queue!(w, SetAttribute(underline.unwrap_or(Attribute::NoUnderline)))?;
         }
         if removed.contains(Modifier::DIM) {
             queue!(w, SetAttribute(Attribute::NormalIntensity))?;
@@ -394,9 +1145,6 @@ impl ModifierDiff {
         if added.contains(Modifier::ITALIC) {
             queue!(w, SetAttribute(Attribute::Italic))?;
         }
-        if added.contains(Modifier::UNDERLINED) {
-            queue!(w, SetAttribute(Attribute::Underlined))?;
-        }
         if added.contains(Modifier::DIM) {
             queue!(w, SetAttribute(Attribute::Dim))?;
         }
@@ -413,3 +1161,144 @@ impl ModifierDiff {
         Ok(())
     }
 }
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor_move(from: Option<(u16, u16)>, to: (u16, u16)) -> String {
+        let mut out = Vec::new();
+        move_cursor(&mut out, from, to);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn cheapest_cursor_moves() {
+        assert_eq!(cursor_move(None, (4, 2)), "\x1b[3;5H");
+        assert_eq!(cursor_move(Some((4, 2)), (4, 2)), "");
+        assert_eq!(cursor_move(Some((4, 2)), (5, 2)), "\x1b[C");
+        if cfg!(unix) {
+            assert_eq!(cursor_move(Some((4, 2)), (0, 3)), "\n\r");
+            assert_eq!(cursor_move(Some((4, 2)), (4, 4)), "\n\n");
+        } else {
+            assert_eq!(cursor_move(Some((4, 2)), (0, 3)), "\x1b[B\r");
+            assert_eq!(cursor_move(Some((4, 2)), (4, 4)), "\x1b[2B");
+        }
+        assert_eq!(cursor_move(Some((40, 2)), (10, 2)), "\x1b[30D");
+        assert_eq!(cursor_move(Some((4, 90)), (70, 2)), "\x1b[3;71H");
+    }
+
+    #[test]
+    fn synchronized_output_detection() {
+        assert_eq!(parse_mode_report(b"\x1b[?2026;2$y\x1b[?62;22c", 2026), Some(2));
+        assert_eq!(parse_mode_report(b"\x1b[?2026;0$y", 2026), Some(0));
+        assert_eq!(parse_mode_report(b"\x1b[?2026;2", 2026), None);
+        assert_eq!(parse_mode_report(b"\x1b[?62;22c", 2026), None);
+
+        assert!(contains_device_attributes(b"\x1b[?62;22c"));
+        assert!(!contains_device_attributes(b"\x1b[?2026;2$y"));
+    }
+
+    #[test]
+    fn scroll_detection() {
+        let lines = |text: &[&str]| {
+            let mut buf = Buffer::new(Area::new(0, 0, 4, text.len() as u16));
+            for (y, line) in text.iter().enumerate() {
+                buf.set_string(0, y as u16, line, crate::Style::default());
+            }
+            buf
+        };
+
+        let mut previous = lines(&["head", "a", "b", "c", "d", "foot"]);
+        let current = lines(&["head", "b", "c", "d", "e", "foot"]);
+        let scroll = Scroll::detect(&previous, &current);
+        assert_eq!(scroll, Some(Scroll { top: 1, bottom: 5, amount: 1, up: true }));
+
+        scroll.unwrap().apply(&mut previous);
+        let mut updates = Vec::new();
+        previous.diff_into(&current, &mut updates);
+        // Only the new line needs to be drawn.
+        assert_eq!(updates, vec![16]);
+
+        let previous = lines(&["a", "b", "c", "d"]);
+        let current = lines(&["z", "a", "b", "c"]);
+        let scroll = Scroll::detect(&previous, &current);
+        assert_eq!(scroll, Some(Scroll { top: 0, bottom: 4, amount: 1, up: false }));
+
+        assert_eq!(Scroll::detect(&previous, &previous), None);
+    }
+
+    #[test]
+    fn runs_are_batched() {
+        let prev = Buffer::new(Area::new(0, 0, 8, 2));
+        let mut next = Buffer::new(Area::new(0, 0, 8, 2));
+        next.set_string(0, 0, "ab", crate::Style::default());
+        next.set_string(4, 0, "cd", crate::Style::default());
+        next.set_string(0, 1, "e", crate::Style::default());
+        let mut updates = Vec::new();
+        prev.diff_into(&next, &mut updates);
+
+        let mut out = Vec::new();
+        write_updates(&next, &updates, Color::Reset, false, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[1;1Hab\x1b[2Ccd\n\re"));
+
+        // Cells without a background of their own are drawn with the clear color.
+        next.get_mut(5, 0).bg = Color::Red;
+        let mut out = Vec::new();
+        write_updates(&next, &updates, Color::Rgb(1, 2, 3), false, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[1;1H\x1b[48;2;1;2;3mab\x1b[2Cc\x1b[48;5;1md"));
+    }
+
+    #[test]
+    fn hyperlink_runs() {
+        let prev = Buffer::new(Area::new(0, 0, 6, 1));
+        let mut next = Buffer::new(Area::new(0, 0, 6, 1));
+        next.set_string(0, 0, "a", crate::Style::default());
+        next.set_hyperlink(1, 0, "bc", "https://a.b", crate::Style::default());
+        next.set_hyperlink(3, 0, "d", "https://c\x1b.d", crate::Style::default());
+        let mut updates = Vec::new();
+        prev.diff_into(&next, &mut updates);
+
+        let mut out = Vec::new();
+        write_updates(&next, &updates, Color::Reset, false, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(concat!(
+            "\x1b[1;1Ha",
+            "\x1b]8;;https://a.b\x1b\\bc",
+            "\x1b]8;;https://c.d\x1b\\d",
+            "\x1b]8;;\x1b\\",
+        )));
+    }
+
+    #[test]
+    fn extended_underlines() {
+        let prev = Buffer::new(Area::new(0, 0, 4, 1));
+        let mut next = Buffer::new(Area::new(0, 0, 4, 1));
+        let style = crate::Style::new().curly_underlined().underline_color(Color::Red);
+        next.set_string(0, 0, "ab", style);
+        next.set_string(2, 0, "c", crate::Style::new().underlined());
+        let mut updates = Vec::new();
+        prev.diff_into(&next, &mut updates);
+
+        let mut out = Vec::new();
+        write_updates(&next, &updates, Color::Reset, true, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[1;1H\x1b[4:3m\x1b[58;5;1mab\x1b[4m\x1b[59mc\x1b[39m"));
+
+        // Without support, every kind of underline is drawn as a plain one, without a color.
+        let mut out = Vec::new();
+        write_updates(&next, &updates, Color::Reset, false, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[1;1H\x1b[4mabc\x1b[39m"));
+
+        assert!(supports_extended_underlines("xterm-kitty", "", None));
+        assert!(supports_extended_underlines("xterm-256color", "", Some(6003)));
+        assert!(supports_extended_underlines("xterm-256color", "WezTerm", None));
+        assert!(!supports_extended_underlines("xterm-256color", "Apple_Terminal", None));
+        assert!(!supports_extended_underlines("tmux-256color", "WezTerm", Some(6003)));
+    }
+}
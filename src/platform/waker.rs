@@ -0,0 +1,112 @@
+//! Waker type
+
+
+
+use std::{any::Any, sync::mpsc};
+
+use crate::Input;
+
+
+
+/// A handle for sending messages to a running [`Program`](crate::Program) from other threads, and
+/// waking up the [`Platform`](super::Platform) it runs on.
+///
+/// Wakers are cheap to clone, so each worker thread can have its own. Messages can be of any
+/// type, and are delivered to [`Program::message`](crate::Program::message) between polls for
+/// input, in the order they were sent.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use dreg::*;
+///
+/// struct Fetched(String);
+///
+/// let terminal = Terminal::new();
+/// let waker = terminal.waker();
+/// std::thread::spawn(move || {
+///     // ...do some slow work...
+///     waker.send(Fetched("done".to_string()));
+/// });
+/// ```
+#[derive(Clone)]
+pub struct Waker {
+    sender: mpsc::Sender<Signal>,
+}
+
+impl Waker {
+    /// Send a message to the program, and wake up the platform so that the program is rendered
+    /// again after handling it.
+    ///
+    /// Returns `false` if the platform has already stopped running.
+    pub fn send<M: Any + Send>(&self, message: M) -> bool {
+        self.signal(Signal::Message(Box::new(message)))
+    }
+
+    /// Wake up the platform so that the program is rendered again, without sending a message.
+    ///
+    /// Returns `false` if the platform has already stopped running.
+    pub fn wake(&self) -> bool {
+        self.signal(Signal::Redraw)
+    }
+
+    /// Run the given future on the current [Tokio](https://tokio.rs) runtime, and send its output
+    /// to the program once it completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from outside of a Tokio runtime.
+    #[cfg(feature = "tokio")]
+    pub fn spawn<F>(&self, future: F) -> tokio::task::JoinHandle<()>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Any + Send,
+    {
+        let waker = self.clone();
+        tokio::spawn(async move {
+            waker.send(future.await);
+        })
+    }
+
+    pub(crate) fn signal(&self, signal: Signal) -> bool {
+        self.sender.send(signal).is_ok()
+    }
+}
+
+
+
+/// Something that wakes up a platform's event loop.
+pub(crate) enum Signal {
+    Input(Input),
+    Message(Box<dyn Any + Send>),
+    Redraw,
+    Error(std::io::Error),
+}
+
+/// Create a [`Waker`], along with the receiving end of its channel.
+pub(crate) fn channel() -> (Waker, mpsc::Receiver<Signal>) {
+    let (sender, receiver) = mpsc::channel();
+    (Waker { sender }, receiver)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waker_delivery() {
+        let (waker, signals) = channel();
+        let other = waker.clone();
+        std::thread::spawn(move || other.send(7_u32)).join().unwrap();
+        assert!(waker.wake());
+
+        let Ok(Signal::Message(message)) = signals.try_recv() else { panic!() };
+        assert_eq!(message.downcast_ref::<u32>(), Some(&7));
+        assert!(matches!(signals.try_recv(), Ok(Signal::Redraw)));
+
+        drop(signals);
+        assert!(!waker.send("too late"));
+    }
+}
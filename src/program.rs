@@ -2,6 +2,13 @@
 
 
 
+mod app;
+mod ui;
+pub use app::*;
+pub use ui::*;
+
+use std::any::Any;
+
 use crate::{Color, Frame, Input};
 
 
@@ -9,7 +16,8 @@ use crate::{Color, Frame, Input};
 /// The object responsible for rendering [`Buffer`]s, handling user [`Input`], and responding to
 /// [`Platform`] requests,
 pub trait Program: 'static {
-    /// Update the program's state. This method is called every tick, regardless of user input.
+    /// Update the program's state. This method is called every tick (right before each frame is
+    /// rendered), regardless of user input.
     fn update(&mut self) {}
 
     /// Render to the program's window.
@@ -19,9 +27,44 @@ pub trait Program: 'static {
     #[allow(unused_variables)]
     fn input(&mut self, input: Input) {}
 
+    /// This function is called whenever a message sent through a [`Waker`](crate::Waker) arrives.
+    ///
+    /// Messages can be of any type, so they need to be downcast to the types the program expects.
+    #[allow(unused_variables)]
+    fn message(&mut self, message: Box<dyn Any + Send>) {}
+
     /// The ['Color'] used to clear the screen with.
+    ///
+    /// This is used as the background of every cell whose background is [`Color::Reset`]. It's
+    /// checked every frame, so it can change at any time. Return [`Color::Reset`] to keep the
+    /// platform's own background color.
     fn clear_color(&self) -> Color { Color::from_rgb(43, 43, 51) }
 
+    /// Whether the platform should keep this program's [`Buffer`](crate::Buffer) between frames.
+    ///
+    /// By default, every frame starts out blank and has to be drawn in full. With retained
+    /// rendering, each frame starts out with the previous frame's contents, and the program only
+    /// needs to redraw the regions it marks with [`Frame::mark_dirty`]. Everything is marked dirty
+    /// for the first frame, and whenever the frame is resized.
+    fn retained_rendering(&self) -> bool { false }
+
+    /// When the platform should render this program.
+    fn render_mode(&self) -> RenderMode { RenderMode::Continuous }
+
     /// The scaling used to render text.
     fn scale(&self) -> f32 { 20.0 }
 }
+
+
+
+/// When a [`Program`] gets rendered.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum RenderMode {
+    /// Render continuously, at a fixed rate, regardless of whether anything changed.
+    #[default]
+    Continuous,
+    /// Only render after user [`Input`], when the window is resized, or when a redraw has been
+    /// requested with [`Frame::request_redraw`] or [`Frame::request_redraw_after`] (which
+    /// [`Animator`](crate::Animator)s do while they run).
+    OnDemand,
+}
@@ -0,0 +1,321 @@
+//! Model/Update/View Application Layer
+
+
+
+use std::{any::Any, collections::VecDeque};
+
+use crate::{Command, Frame, Input, Program, RenderMode, Waker};
+
+
+
+/// An application's state, structured around messages.
+///
+/// Instead of mutating its state wherever it sees fit, a model turns everything that happens
+/// (user [`Input`], results from background tasks, etc.) into messages, and only changes in
+/// [`Model::update`]. Side effects are requested by returning a [`Cmd`] from `update`, and
+/// [`Model::view`] draws the current state without changing it.
+///
+/// Models are run by wrapping them in an [`App`], which is itself a [`Program`], so they can be
+/// used anywhere a program can.
+pub trait Model: 'static {
+    /// The messages that this model handles.
+    type Message: Send + 'static;
+
+    /// Called once, before the first frame is rendered.
+    fn init(&mut self) -> Cmd<Self::Message> {
+        Cmd::none()
+    }
+
+    /// Handle a message, returning any side effects that should be performed.
+    fn update(&mut self, message: Self::Message) -> Cmd<Self::Message>;
+
+    /// Draw the model's current state to the frame.
+    fn view(&self, frame: &mut Frame);
+
+    /// Turn user [`Input`] into a message. Input that doesn't produce a message is ignored.
+    #[allow(unused_variables)]
+    fn input(&self, input: Input) -> Option<Self::Message> {
+        None
+    }
+}
+
+
+
+/// The side effects requested by a [`Model`] when it handles a message.
+///
+/// Commands can be combined with [`Cmd::and`] or [`Cmd::batch`], and are performed in the order
+/// they were added.
+pub struct Cmd<M> {
+    effects: Vec<Effect<M>>,
+}
+
+enum Effect<M> {
+    Quit,
+    Command(Command),
+    Message(M),
+    Spawn(Box<dyn FnOnce() -> M + Send>),
+}
+
+impl<M> Cmd<M> {
+    /// Do nothing.
+    pub fn none() -> Self {
+        Self { effects: Vec::new() }
+    }
+
+    /// Exit the program at the end of the next frame.
+    pub fn quit() -> Self {
+        Self { effects: vec![Effect::Quit] }
+    }
+
+    /// Set the window's title.
+    pub fn set_title(title: impl Into<String>) -> Self {
+        Self::command(Command::SetTitle(title.into()))
+    }
+
+    /// Send a [`Command`] to the platform with the next frame.
+    pub fn command(command: Command) -> Self {
+        Self { effects: vec![Effect::Command(command)] }
+    }
+
+    /// Handle another message right away.
+    pub fn message(message: M) -> Self {
+        Self { effects: vec![Effect::Message(message)] }
+    }
+
+    /// Run the given function on a new thread, and handle the message it returns once it's done.
+    pub fn spawn(task: impl FnOnce() -> M + Send + 'static) -> Self {
+        Self { effects: vec![Effect::Spawn(Box::new(task))] }
+    }
+
+    /// Combine a set of commands into one.
+    pub fn batch(cmds: impl IntoIterator<Item = Self>) -> Self {
+        Self {
+            effects: cmds.into_iter().flat_map(|cmd| cmd.effects).collect(),
+        }
+    }
+
+    /// Perform another command after this one.
+    pub fn and(mut self, other: Self) -> Self {
+        self.effects.extend(other.effects);
+        self
+    }
+
+    /// Whether this command does nothing.
+    pub fn is_none(&self) -> bool {
+        self.effects.is_empty()
+    }
+}
+
+impl<M> Default for Cmd<M> {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+
+
+/// A [`Program`] that runs a [`Model`].
+///
+/// Messages sent through the given [`Waker`] are handled by the model, as long as they have the
+/// model's message type. Apps render on demand by default, since their state can only change when
+/// a message arrives.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use dreg::*;
+///
+/// struct Counter(i64);
+///
+/// enum Message {
+///     Increment,
+///     Quit,
+/// }
+///
+/// impl Model for Counter {
+///     type Message = Message;
+///
+///     fn update(&mut self, message: Message) -> Cmd<Message> {
+///         match message {
+///             Message::Increment => self.0 += 1,
+///             Message::Quit => return Cmd::quit(),
+///         }
+///         Cmd::none()
+///     }
+///
+///     fn view(&self, frame: &mut Frame) {
+///         frame.buffer.set_string(0, 0, format!("{}", self.0), Style::default());
+///     }
+///
+///     fn input(&self, input: Input) -> Option<Message> {
+///         match input {
+///             Input::KeyDown(Scancode::SPACE) => Some(Message::Increment),
+///             Input::KeyDown(Scancode::Q) => Some(Message::Quit),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let terminal = Terminal::new();
+/// let app = App::new(Counter(0), terminal.waker());
+/// terminal.run(app).unwrap();
+/// ```
+pub struct App<M: Model> {
+    model: M,
+    waker: Waker,
+    render_mode: RenderMode,
+    initialized: bool,
+    should_exit: bool,
+    commands: Vec<Command>,
+}
+
+impl<M: Model> App<M> {
+    /// Create a new app for the given model. The waker should belong to the platform that the app
+    /// will run on.
+    pub fn new(model: M, waker: Waker) -> Self {
+        Self {
+            model,
+            waker,
+            render_mode: RenderMode::OnDemand,
+            initialized: false,
+            should_exit: false,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Set when the app should be rendered.
+    pub fn with_render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    /// Get the app's model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Handle a message, along with any messages produced by the commands it results in.
+    pub fn dispatch(&mut self, message: M::Message) {
+        let cmd = self.model.update(message);
+        self.perform(cmd);
+    }
+
+    fn perform(&mut self, cmd: Cmd<M::Message>) {
+        let mut effects = VecDeque::from(cmd.effects);
+        while let Some(effect) = effects.pop_front() {
+            match effect {
+                Effect::Quit => self.should_exit = true,
+                Effect::Command(command) => self.commands.push(command),
+                Effect::Message(message) => effects.extend(self.model.update(message).effects),
+                Effect::Spawn(task) => {
+                    let waker = self.waker.clone();
+                    std::thread::spawn(move || {
+                        waker.send(task());
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<M: Model> Program for App<M> {
+    fn render(&mut self, frame: &mut Frame) {
+        if !self.initialized {
+            self.initialized = true;
+            let cmd = self.model.init();
+            self.perform(cmd);
+        }
+        frame.commands.append(&mut self.commands);
+        frame.should_exit |= self.should_exit;
+        self.model.view(frame);
+    }
+
+    fn input(&mut self, input: Input) {
+        if let Some(message) = self.model.input(input) {
+            self.dispatch(message);
+        }
+    }
+
+    fn message(&mut self, message: Box<dyn Any + Send>) {
+        if let Ok(message) = message.downcast::<M::Message>() {
+            self.dispatch(*message);
+        }
+    }
+
+    fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{buffer, text, with_frame};
+
+    struct Counter(i64);
+
+    enum Message {
+        Add(i64),
+        Double,
+        Quit,
+    }
+
+    impl Model for Counter {
+        type Message = Message;
+
+        fn init(&mut self) -> Cmd<Message> {
+            Cmd::set_title("counter")
+        }
+
+        fn update(&mut self, message: Message) -> Cmd<Message> {
+            match message {
+                Message::Add(n) => self.0 += n,
+                Message::Double => return Cmd::message(Message::Add(self.0)),
+                Message::Quit => return Cmd::quit(),
+            }
+            Cmd::none()
+        }
+
+        fn view(&self, frame: &mut Frame) {
+            frame.buffer.set_string(0, 0, self.0.to_string(), crate::Style::default());
+        }
+
+        fn input(&self, input: Input) -> Option<Message> {
+            match input {
+                Input::WheelUp => Some(Message::Add(1)),
+                _ => None,
+            }
+        }
+    }
+
+    fn render(app: &mut App<Counter>) -> (String, Vec<Command>, bool) {
+        let mut buffer = buffer(4, 1);
+        let (commands, should_exit) = with_frame(&mut buffer, |frame| {
+            app.render(frame);
+            (std::mem::take(frame.commands), frame.should_exit)
+        });
+        (text(&buffer).trim().to_string(), commands, should_exit)
+    }
+
+    #[test]
+    fn app_updates() {
+        let terminal = crate::Terminal::new();
+        let mut app = App::new(Counter(0), terminal.waker());
+        assert_eq!(
+            render(&mut app),
+            ("0".to_string(), vec![Command::SetTitle("counter".to_string())], false),
+        );
+
+        app.input(Input::WheelUp);
+        app.input(Input::WheelDown);
+        app.message(Box::new(Message::Add(2)));
+        app.message(Box::new("ignored"));
+        app.dispatch(Message::Double);
+        assert_eq!(render(&mut app), ("6".to_string(), vec![], false));
+
+        app.dispatch(Message::Quit);
+        assert!(render(&mut app).2);
+    }
+}
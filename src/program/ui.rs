@@ -0,0 +1,644 @@
+//! Immediate-Mode UI
+
+
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::RangeInclusive,
+};
+
+use unicode_segmentation::UnicodeSegmentation as _;
+use unicode_width::UnicodeWidthStr as _;
+
+use crate::{Area, Buffer, Frame, InputContext, MouseButton, Scancode, Style};
+
+
+
+/// A widget's identity, which stays the same between frames as long as the widget is created the
+/// same way.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct WidgetId(u64);
+
+/// The parts of a [`Ui`] that need to persist between frames.
+///
+/// Programs should keep one of these around, and pass it to [`Ui::new`] every frame.
+#[derive(Default)]
+pub struct UiState {
+    hot: Option<WidgetId>,
+    active: Option<WidgetId>,
+    focused: Option<WidgetId>,
+    focus_order: Vec<WidgetId>,
+    text_cursor: usize,
+    /// The styles used to draw widgets.
+    pub style: UiStyle,
+}
+
+impl UiState {
+    /// The widget under the mouse cursor during the last frame.
+    pub fn hot(&self) -> Option<WidgetId> {
+        self.hot
+    }
+
+    /// The widget being interacted with (usually held down with the mouse).
+    pub fn active(&self) -> Option<WidgetId> {
+        self.active
+    }
+
+    /// The widget with keyboard focus.
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focused
+    }
+
+    /// Give keyboard focus to the given widget, or remove it from every widget.
+    pub fn set_focus(&mut self, id: Option<WidgetId>) {
+        if self.focused != id {
+            self.focused = id;
+            self.text_cursor = usize::MAX;
+        }
+    }
+}
+
+/// The styles a [`Ui`] draws its widgets with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UiStyle {
+    /// Used for widgets that aren't being interacted with.
+    pub normal: Style,
+    /// Used for the widget under the mouse cursor.
+    pub hot: Style,
+    /// Used for the widget being held down.
+    pub active: Style,
+    /// Used for the widget with keyboard focus.
+    pub focused: Style,
+}
+
+impl Default for UiStyle {
+    fn default() -> Self {
+        Self {
+            normal: Style::new(),
+            hot: Style::new().bold(),
+            active: Style::new().reversed(),
+            focused: Style::new().underlined(),
+        }
+    }
+}
+
+
+
+/// How the user interacted with a widget during a frame.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Response {
+    /// Whether the mouse cursor is over the widget.
+    pub hovered: bool,
+    /// Whether the left mouse button was pressed on the widget this frame.
+    pub pressed: bool,
+    /// Whether the left mouse button was pressed on the widget, and then released over it, this
+    /// frame.
+    pub clicked: bool,
+    /// Whether the left mouse button is being held down after being pressed on the widget.
+    pub dragging: bool,
+    /// Whether the widget has keyboard focus.
+    pub focused: bool,
+}
+
+/// An immediate-mode UI context.
+///
+/// Widgets are created by calling methods on the UI every frame, which draw the widget to the
+/// frame and return whether the user interacted with it. Each widget is given a [`WidgetId`] based
+/// on its label and the order it was created in, which is used to track which widget is hot (under
+/// the mouse), active (held down), and focused. Focus moves between widgets with Tab and
+/// Shift+Tab, in the order they were created.
+///
+/// The UI finishes the frame when it's dropped, so the [`InputContext`] should only be ended after
+/// that.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// struct Settings {
+///     ui: UiState,
+///     input: InputContext,
+///     enabled: bool,
+///     volume: f64,
+///     name: String,
+/// }
+///
+/// impl Program for Settings {
+///     fn render(&mut self, frame: &mut Frame) {
+///         let mut ui = Ui::new(frame, &self.input, &mut self.ui);
+///         ui.checkbox(Area::new(0, 0, 20, 1), "Enabled", &mut self.enabled);
+///         ui.slider(Area::new(0, 1, 20, 1), &mut self.volume, 0.0..=1.0);
+///         ui.text_edit(Area::new(0, 2, 20, 1), &mut self.name);
+///         if ui.button(Area::new(0, 3, 6, 1), "Quit") {
+///             ui.frame().should_exit = true;
+///         }
+///         drop(ui);
+///         self.input.end_frame();
+///     }
+///
+///     fn input(&mut self, input: Input) {
+///         self.input.handle_input(input);
+///     }
+/// }
+/// ```
+pub struct Ui<'a, 'f> {
+    frame: &'a mut Frame<'f>,
+    input: &'a InputContext,
+    state: &'a mut UiState,
+    scopes: Vec<u64>,
+    seen: HashMap<u64, u32>,
+    focusables: Vec<WidgetId>,
+    hot: Option<WidgetId>,
+    claimed_press: bool,
+}
+
+impl<'a, 'f> Ui<'a, 'f> {
+    /// Start laying out a frame's widgets.
+    ///
+    /// Any Tab or Shift+Tab presses in the input context move the focus, based on the order of the
+    /// widgets in the previous frame.
+    pub fn new(frame: &'a mut Frame<'f>, input: &'a InputContext, state: &'a mut UiState) -> Self {
+        let step = input.keys_pressed().iter()
+            .filter(|press| press.code == Scancode::TAB)
+            .map(|press| if press.shift { -1 } else { 1 })
+            .sum::<i64>();
+        let len = state.focus_order.len() as i64;
+        if step != 0 && len > 0 {
+            let current = state.focused
+                .and_then(|id| state.focus_order.iter().position(|other| *other == id))
+                .map_or(if step > 0 { -1 } else { len }, |index| index as i64);
+            let next = state.focus_order[(current + step).rem_euclid(len) as usize];
+            state.set_focus(Some(next));
+        }
+
+        Self {
+            frame,
+            input,
+            state,
+            scopes: Vec::new(),
+            seen: HashMap::new(),
+            focusables: Vec::new(),
+            hot: None,
+            claimed_press: false,
+        }
+    }
+
+    /// Get the frame the UI draws to.
+    pub fn frame(&mut self) -> &mut Frame<'f> {
+        self.frame
+    }
+
+    /// Get the input the UI responds to.
+    pub fn input(&self) -> &InputContext {
+        self.input
+    }
+
+    /// Get the UI's persistent state.
+    pub fn state(&mut self) -> &mut UiState {
+        self.state
+    }
+
+    /// Create a [`WidgetId`] from the given source, within the current scope.
+    ///
+    /// Creating several IDs from the same source gives each of them a different ID, based on the
+    /// order they were created in.
+    pub fn id(&mut self, source: impl Hash) -> WidgetId {
+        let mut hasher = DefaultHasher::new();
+        self.scopes.last().hash(&mut hasher);
+        source.hash(&mut hasher);
+        let base = hasher.finish();
+
+        let count = self.seen.entry(base).or_insert(0);
+        (base, *count).hash(&mut hasher);
+        *count += 1;
+        WidgetId(hasher.finish())
+    }
+
+    /// Create widgets within a scope named after the given source.
+    ///
+    /// Widgets in different scopes can share labels without changing each other's IDs, which is
+    /// useful for keeping IDs stable in lists whose items can be added or removed.
+    pub fn scope<R>(&mut self, source: impl Hash, f: impl FnOnce(&mut Self) -> R) -> R {
+        let mut hasher = DefaultHasher::new();
+        self.scopes.last().hash(&mut hasher);
+        source.hash(&mut hasher);
+        self.scopes.push(hasher.finish());
+        let result = f(self);
+        self.scopes.pop();
+        result
+    }
+
+    /// Handle the mouse and focus interactions for a focusable widget covering the given area.
+    pub fn interact(&mut self, id: WidgetId, area: Area) -> Response {
+        self.focusables.push(id);
+
+        let hovered = self.input.hovered(&area);
+        if hovered {
+            self.hot = Some(id);
+        }
+        let pressed = hovered && self.input.mouse_pressed(MouseButton::Left);
+        if pressed {
+            self.claimed_press = true;
+            self.state.active = Some(id);
+            self.state.set_focus(Some(id));
+        }
+        let active = self.state.active == Some(id);
+
+        Response {
+            hovered,
+            pressed,
+            clicked: active && hovered && self.input.mouse_released(MouseButton::Left),
+            dragging: active && self.input.is_mouse_button_down(MouseButton::Left),
+            focused: self.state.focused == Some(id),
+        }
+    }
+
+    /// A button that returns `true` when it's clicked, or activated with Enter or Space while
+    /// focused.
+    pub fn button(&mut self, area: Area, label: &str) -> bool {
+        let id = self.id(("button", label));
+        let response = self.interact(id, area);
+        let style = self.style_for(&response);
+
+        fill(self.frame.buffer, area, style);
+        let width = label.width().min(area.w as usize) as u16;
+        let x = area.x + (area.w - width) / 2;
+        self.frame.buffer.set_stringn(x, area.y + area.h / 2, label, area.w as usize, style);
+
+        response.clicked || (response.focused && self.activated())
+    }
+
+    /// A checkbox that toggles `checked` when it's clicked, or activated with Enter or Space while
+    /// focused. Returns `true` if the value changed.
+    pub fn checkbox(&mut self, area: Area, label: &str, checked: &mut bool) -> bool {
+        let id = self.id(("checkbox", label));
+        let response = self.interact(id, area);
+        let changed = response.clicked || (response.focused && self.activated());
+        if changed {
+            *checked = !*checked;
+        }
+        let style = self.style_for(&response);
+
+        fill(self.frame.buffer, area, style);
+        let text = format!("[{}] {label}", if *checked { 'x' } else { ' ' });
+        self.frame.buffer.set_stringn(area.x, area.y, text, area.w as usize, style);
+
+        changed
+    }
+
+    /// A horizontal slider for picking a value within the given range.
+    ///
+    /// The value can be dragged with the mouse, or moved with the arrow keys (and Home/End) while
+    /// focused. Returns `true` if the value changed.
+    pub fn slider(&mut self, area: Area, value: &mut f64, range: RangeInclusive<f64>) -> bool {
+        let id = self.id("slider");
+        let response = self.interact(id, area);
+        let (min, max) = (*range.start(), *range.end());
+        let steps = area.w.saturating_sub(1).max(1) as f64;
+        let previous = *value;
+
+        if response.dragging || response.pressed {
+            if let Some((x, _)) = self.input.mouse_pos() {
+                let t = (x.saturating_sub(area.x) as f64 / steps).clamp(0.0, 1.0);
+                *value = min + (max - min) * t;
+            }
+        }
+        if response.focused {
+            for press in self.input.keys_pressed() {
+                match press.code {
+                    Scancode::LEFT => *value -= (max - min) / steps,
+                    Scancode::RIGHT => *value += (max - min) / steps,
+                    Scancode::HOME => *value = min,
+                    Scancode::END => *value = max,
+                    _ => {}
+                }
+            }
+        }
+        *value = value.clamp(min.min(max), max.max(min));
+
+        let style = self.style_for(&response);
+        let t = if max == min { 0.0 } else { (*value - min) / (max - min) };
+        let thumb = area.x + (t * steps).round() as u16;
+        for x in area.left()..area.right() {
+            if let Some(cell) = self.frame.buffer.try_get_mut(x, area.y) {
+                cell.set_symbol(if x == thumb { "█" } else { "━" }).set_style(style);
+            }
+        }
+
+        *value != previous
+    }
+
+    /// A single-line text field.
+    ///
+    /// While focused, typed text is inserted at the cursor, which can be moved with the arrow keys
+    /// (and Home/End). Returns `true` if the text changed.
+    pub fn text_edit(&mut self, area: Area, text: &mut String) -> bool {
+        let id = self.id("text_edit");
+        let response = self.interact(id, area);
+        let mut graphemes = text.graphemes(true).map(str::to_string).collect::<Vec<_>>();
+        let mut changed = false;
+
+        if response.focused {
+            let mut cursor = self.state.text_cursor.min(graphemes.len());
+            if response.pressed {
+                if let Some((x, _)) = self.input.mouse_pos() {
+                    // Place the cursor before the grapheme under the mouse.
+                    let offset = scroll_offset(&graphemes, cursor, area.w);
+                    let column = x.saturating_sub(area.x) as usize;
+                    let mut right = 0;
+                    cursor = offset + graphemes[offset..].iter()
+                        .take_while(|grapheme| {
+                            right += grapheme.width();
+                            right <= column
+                        })
+                        .count();
+                }
+            }
+            for press in self.input.keys_pressed() {
+                match press.code {
+                    Scancode::BACKSPACE if cursor > 0 => {
+                        cursor -= 1;
+                        graphemes.remove(cursor);
+                        changed = true;
+                    }
+                    Scancode::DELETE if cursor < graphemes.len() => {
+                        graphemes.remove(cursor);
+                        changed = true;
+                    }
+                    Scancode::LEFT => cursor = cursor.saturating_sub(1),
+                    Scancode::RIGHT => cursor = (cursor + 1).min(graphemes.len()),
+                    Scancode::HOME => cursor = 0,
+                    Scancode::END => cursor = graphemes.len(),
+                    _ => if let Some(c) = press.to_char() {
+                        graphemes.insert(cursor, c.to_string());
+                        cursor += 1;
+                        changed = true;
+                    }
+                }
+            }
+            self.state.text_cursor = cursor;
+        }
+        if changed {
+            *text = graphemes.concat();
+        }
+
+        let style = self.style_for(&response);
+        fill(self.frame.buffer, area, style);
+        let cursor = if response.focused { self.state.text_cursor } else { 0 };
+        let offset = scroll_offset(&graphemes, cursor, area.w);
+        let visible = graphemes[offset..].concat();
+        self.frame.buffer.set_stringn(area.x, area.y, visible, area.w as usize, style);
+        if response.focused && area.w > 0 {
+            let column = graphemes[offset..cursor].concat().width() as u16;
+            self.frame.cursor = Some((area.x + column, area.y));
+        }
+
+        changed
+    }
+
+    fn activated(&self) -> bool {
+        self.input.key_pressed(Scancode::ENTER) || self.input.key_pressed(Scancode::SPACE)
+    }
+
+    fn style_for(&self, response: &Response) -> Style {
+        let style = &self.state.style;
+        if response.dragging {
+            style.active
+        } else if response.hovered {
+            style.hot
+        } else if response.focused {
+            style.focused
+        } else {
+            style.normal
+        }
+    }
+}
+
+impl Drop for Ui<'_, '_> {
+    fn drop(&mut self) {
+        self.state.hot = self.hot;
+        self.state.focus_order = std::mem::take(&mut self.focusables);
+        if !self.input.is_mouse_button_down(MouseButton::Left) {
+            self.state.active = None;
+        }
+        if self.input.mouse_pressed(MouseButton::Left) && !self.claimed_press {
+            self.state.set_focus(None);
+        }
+        if self.state.focused.is_some_and(|id| !self.state.focus_order.contains(&id)) {
+            self.state.set_focus(None);
+        }
+    }
+}
+
+/// The index of the first grapheme shown in a text field `w` cells wide, which scrolls just far
+/// enough to keep the grapheme at `cursor` visible, with a cell left over for the caret after it.
+fn scroll_offset(graphemes: &[String], cursor: usize, w: u16) -> usize {
+    let mut room = w.saturating_sub(1) as usize;
+    let mut offset = cursor;
+    while offset > 0 {
+        let Some(rest) = room.checked_sub(graphemes[offset - 1].width()) else {
+            break;
+        };
+        room = rest;
+        offset -= 1;
+    }
+    offset
+}
+
+fn fill(buf: &mut Buffer, area: Area, style: Style) {
+    let area = area.intersection(buf.area);
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            buf.get_mut(x, y).set_symbol(" ").set_style(style);
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Input;
+    use crate::test_utils::{row, with_frame};
+
+    struct Harness {
+        buffer: Buffer,
+        input: InputContext,
+        state: UiState,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            Self {
+                buffer: Buffer::new(Area::new(0, 0, 10, 3)),
+                input: InputContext::default(),
+                state: UiState::default(),
+            }
+        }
+
+        fn frame(&mut self, inputs: &[Input], f: impl FnOnce(&mut Ui)) -> Option<(u16, u16)> {
+            for input in inputs {
+                self.input.handle_input(*input);
+            }
+            self.buffer.reset();
+            let cursor = with_frame(&mut self.buffer, |frame| {
+                f(&mut Ui::new(frame, &self.input, &mut self.state));
+                frame.cursor
+            });
+            self.input.end_frame();
+            cursor
+        }
+    }
+
+    #[test]
+    fn widget_ids() {
+        let mut h = Harness::new();
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            h.frame(&[], |ui| {
+                let first = ui.id("a");
+                let second = ui.id("a");
+                let scoped = ui.scope(1, |ui| ui.id("a"));
+                assert_ne!(first, second);
+                assert_ne!(first, scoped);
+                ids.push([first, second, scoped]);
+            });
+        }
+        assert_eq!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn clicks_and_focus() {
+        let mut h = Harness::new();
+        let button = Area::new(0, 0, 4, 1);
+        let checkbox = Area::new(0, 1, 10, 1);
+        let mut checked = false;
+
+        let mut clicked = false;
+        h.frame(&[Input::MouseMove(1, 0), Input::MouseDown(MouseButton::Left)], |ui| {
+            clicked = ui.button(button, "ok");
+            ui.checkbox(checkbox, "on", &mut checked);
+        });
+        assert!(!clicked);
+        h.frame(&[Input::MouseUp(MouseButton::Left)], |ui| {
+            clicked = ui.button(button, "ok");
+            ui.checkbox(checkbox, "on", &mut checked);
+        });
+        assert!(clicked);
+
+        // Tab moves the focus from the button to the checkbox, which Space then toggles.
+        h.frame(&[Input::KeyDown(Scancode::TAB), Input::KeyDown(Scancode::SPACE)], |ui| {
+            ui.button(button, "ok");
+            assert!(ui.checkbox(checkbox, "on", &mut checked));
+        });
+        assert!(checked);
+        assert!(row(&h.buffer, 1).starts_with("[x"));
+
+        // Shift+Tab wraps around backwards.
+        let inputs = [Input::KeyDown(Scancode::L_SHIFT), Input::KeyDown(Scancode::TAB)];
+        h.frame(&inputs, |ui| {
+            ui.button(button, "ok");
+            ui.checkbox(checkbox, "on", &mut checked);
+            assert!(ui.state().focused().is_some());
+        });
+        h.frame(&[Input::KeyDown(Scancode::ENTER)], |ui| {
+            assert!(ui.button(button, "ok"));
+            ui.checkbox(checkbox, "on", &mut checked);
+        });
+    }
+
+    #[test]
+    fn wide_graphemes() {
+        let mut h = Harness::new();
+        h.frame(&[], |ui| {
+            ui.button(Area::new(0, 0, 6, 1), "界界");
+        });
+        assert_eq!(row(&h.buffer, 0), " 界 界      ");
+
+        let mut h = Harness::new();
+        let field = Area::new(0, 1, 3, 1);
+        let mut text = String::from("界界");
+        h.frame(&[], |ui| {
+            ui.text_edit(field, &mut text);
+        });
+        // Only the last grapheme fits before the caret at the end of the text.
+        let cursor = h.frame(&[Input::KeyDown(Scancode::TAB), Input::KeyDown(Scancode::END)], |ui| {
+            ui.text_edit(field, &mut text);
+        });
+        assert_eq!(cursor, Some((2, 1)));
+        assert!(row(&h.buffer, 1).starts_with("界 "));
+
+        // Clicking either half of a wide grapheme puts the cursor before it.
+        let inputs = [Input::MouseMove(1, 1), Input::MouseDown(MouseButton::Left)];
+        let cursor = h.frame(&inputs, |ui| {
+            ui.text_edit(field, &mut text);
+        });
+        assert_eq!(h.state.text_cursor, 1);
+        assert_eq!(cursor, Some((2, 1)));
+    }
+
+    #[test]
+    fn sliders_and_text() {
+        let mut h = Harness::new();
+        let slider = Area::new(0, 0, 5, 1);
+        let field = Area::new(0, 1, 3, 1);
+        let mut value = 0.0;
+        let mut text = String::from("ab");
+
+        h.frame(&[Input::MouseMove(2, 0), Input::MouseDown(MouseButton::Left)], |ui| {
+            assert!(ui.slider(slider, &mut value, 0.0..=8.0));
+            ui.text_edit(field, &mut text);
+        });
+        assert_eq!(value, 4.0);
+        h.frame(&[Input::KeyDown(Scancode::RIGHT), Input::MouseUp(MouseButton::Left)], |ui| {
+            ui.slider(slider, &mut value, 0.0..=8.0);
+            ui.text_edit(field, &mut text);
+        });
+        assert_eq!(value, 6.0);
+
+        let inputs = [
+            Input::KeyDown(Scancode::TAB),
+            Input::KeyDown(Scancode::L_SHIFT),
+            Input::KeyDown(Scancode::C),
+            Input::KeyDown(Scancode::K_1),
+        ];
+        let cursor = h.frame(&inputs, |ui| {
+            ui.slider(slider, &mut value, 0.0..=8.0);
+            assert!(ui.text_edit(field, &mut text));
+        });
+        assert_eq!(text, "abC1");
+        assert_eq!(cursor, Some((2, 1)));
+        assert!(row(&h.buffer, 1).starts_with("C"));
+
+        let inputs = [Input::KeyDown(Scancode::HOME), Input::KeyDown(Scancode::DELETE)];
+        let cursor = h.frame(&inputs, |ui| {
+            ui.slider(slider, &mut value, 0.0..=8.0);
+            assert!(ui.text_edit(field, &mut text));
+        });
+        assert_eq!(text, "bC1");
+        assert_eq!(cursor, Some((0, 1)));
+
+        // Releasing shift (or reaching the end of the frame) stops the next key being shifted,
+        // and chords don't type anything.
+        let inputs = [
+            Input::KeyDown(Scancode::L_SHIFT),
+            Input::KeyUp(Scancode::L_SHIFT),
+            Input::KeyDown(Scancode::A),
+            Input::KeyDown(Scancode::L_CTRL),
+            Input::KeyDown(Scancode::B),
+            Input::KeyDown(Scancode::L_SHIFT),
+        ];
+        h.frame(&inputs, |ui| {
+            ui.slider(slider, &mut value, 0.0..=8.0);
+            ui.text_edit(field, &mut text);
+        });
+        h.frame(&[Input::KeyDown(Scancode::D)], |ui| {
+            ui.slider(slider, &mut value, 0.0..=8.0);
+            ui.text_edit(field, &mut text);
+        });
+        assert_eq!(text, "adbC1");
+    }
+}
@@ -5,6 +5,20 @@
 
 
 
+pub mod canvas;
+pub mod chart;
+pub mod clear;
+pub mod gradient;
+pub mod scroll_view;
+pub mod scrollbar;
 pub mod shapes;
+pub mod spinner;
 
+pub use canvas::*;
+pub use chart::*;
+pub use clear::*;
+pub use gradient::*;
+pub use scroll_view::*;
+pub use scrollbar::*;
 pub use shapes::*;
+pub use spinner::*;
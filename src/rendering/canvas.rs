@@ -0,0 +1,639 @@
+//! Drawing Canvas
+
+
+
+use crate::{Area, Color, RenderTarget, Style};
+
+
+
+/// A drawing surface with a floating-point coordinate space.
+///
+/// Shapes drawn on a canvas are rasterized onto a grid of "pixels" whose resolution is set by the
+/// canvas' [`Marker`]. The Y axis points up, so `y_bounds[0]` is at the bottom of the canvas.
+///
+/// When several shapes hit the same cell, the cell takes the color that covers most of its
+/// pixels, with ties going to whatever was drawn last. With [`Marker::HalfBlock`], the top and
+/// bottom halves of a cell keep their own colors.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// let mut buf = Buffer::empty();
+/// buf.resize(Area::new(0, 0, 20, 10));
+///
+/// Canvas::new([0.0, 10.0], [0.0, 10.0])
+///     .with_marker(Marker::Braille)
+///     .render(buf.area, &mut buf, |painter| {
+///         painter.draw(&Line { x1: 0.0, y1: 0.0, x2: 10.0, y2: 10.0, color: Color::Red });
+///         painter.draw(&Circle { x: 5.0, y: 5.0, radius: 3.0, color: Color::Blue });
+///         painter.print(0.0, 10.0, "origin", Style::default());
+///     });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Canvas {
+    /// The lower and upper bounds of the canvas' X axis.
+    pub x_bounds: [f64; 2],
+    /// The lower and upper bounds of the canvas' Y axis.
+    pub y_bounds: [f64; 2],
+    /// The marker used to draw pixels, which determines the canvas' resolution.
+    pub marker: Marker,
+}
+
+impl Canvas {
+    /// Create a new canvas with the given bounds, that uses [`Marker::Braille`].
+    pub const fn new(x_bounds: [f64; 2], y_bounds: [f64; 2]) -> Self {
+        Self {
+            x_bounds,
+            y_bounds,
+            marker: Marker::Braille,
+        }
+    }
+
+    /// Set the marker for this canvas.
+    pub const fn with_marker(mut self, marker: Marker) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    /// Render this canvas, drawing on it with the given function.
+    ///
+    /// Cells that aren't drawn on are left as they are.
+    pub fn render<F>(self, area: Area, buf: &mut impl RenderTarget, draw: F)
+    where
+        F: FnOnce(&mut Painter),
+    {
+        let area = area.intersection(buf.area());
+        if area.is_empty() {
+            return;
+        }
+        let mut painter = Painter {
+            grid: Grid::new(area.w, area.h, self.marker),
+            x_bounds: self.x_bounds,
+            y_bounds: self.y_bounds,
+            labels: Vec::new(),
+        };
+        draw(&mut painter);
+
+        painter.grid.render(area, buf);
+
+        let [x_min, x_max] = self.x_bounds;
+        let [y_min, y_max] = self.y_bounds;
+        for label in painter.labels {
+            if !(x_min..=x_max).contains(&label.x) || !(y_min..=y_max).contains(&label.y) {
+                continue;
+            }
+            let x = area.x
+                + ((label.x - x_min) / (x_max - x_min) * (area.w - 1) as f64).round() as u16;
+            let y = area.y
+                + ((y_max - label.y) / (y_max - y_min) * (area.h - 1) as f64).round() as u16;
+            buf.set_stringn(
+                x,
+                y,
+                &label.text,
+                area.right().saturating_sub(x) as usize,
+                label.style,
+            );
+        }
+    }
+}
+
+
+
+/// The object used to draw [`Shape`]s on a [`Canvas`].
+pub struct Painter {
+    grid: Grid,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+    labels: Vec<Label>,
+}
+
+struct Label {
+    x: f64,
+    y: f64,
+    text: String,
+    style: Style,
+}
+
+impl Painter {
+    /// Draw the given shape.
+    pub fn draw<S: Shape + ?Sized>(&mut self, shape: &S) {
+        shape.draw(self);
+    }
+
+    /// Print some text with its first character at the given canvas coordinates.
+    ///
+    /// Text is printed over any shapes, regardless of when it was printed.
+    pub fn print(&mut self, x: f64, y: f64, text: impl Into<String>, style: Style) {
+        self.labels.push(Label { x, y, text: text.into(), style });
+    }
+
+    /// The canvas' X and Y bounds.
+    pub fn bounds(&self) -> ([f64; 2], [f64; 2]) {
+        (self.x_bounds, self.y_bounds)
+    }
+
+    /// The number of pixels along the canvas' X and Y axes.
+    pub fn resolution(&self) -> (usize, usize) {
+        self.grid.resolution()
+    }
+
+    /// Convert canvas coordinates to (fractional) pixel coordinates, with the origin at the
+    /// top-left pixel.
+    ///
+    /// Returns `None` if the canvas' bounds are empty.
+    pub fn to_pixel(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let [x_min, x_max] = self.x_bounds;
+        let [y_min, y_max] = self.y_bounds;
+        if x_max <= x_min || y_max <= y_min {
+            return None;
+        }
+        let (res_w, res_h) = self.grid.resolution();
+        Some((
+            (x - x_min) / (x_max - x_min) * (res_w.saturating_sub(1)) as f64,
+            (y_max - y) / (y_max - y_min) * (res_h.saturating_sub(1)) as f64,
+        ))
+    }
+
+    /// Paint the pixel at the given canvas coordinates, if it's within the canvas' bounds.
+    pub fn point(&mut self, x: f64, y: f64, color: Color) {
+        if !self.contains(x, y) {
+            return;
+        }
+        if let Some((px, py)) = self.to_pixel(x, y) {
+            self.paint(px.round() as isize, py.round() as isize, color);
+        }
+    }
+
+    /// Paint the pixel at the given pixel coordinates. Pixels outside the canvas are ignored.
+    pub fn paint(&mut self, x: isize, y: isize, color: Color) {
+        self.grid.paint(x, y, color);
+    }
+
+    /// Paint a line between two pixels.
+    pub fn paint_line(&mut self, from: (isize, isize), to: (isize, isize), color: Color) {
+        let (x0, y0) = from;
+        let (x1, y1) = to;
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let (mut x, mut y) = (x0, y0);
+        let mut err = dx + dy;
+        loop {
+            self.paint(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    fn contains(&self, x: f64, y: f64) -> bool {
+        (self.x_bounds[0]..=self.x_bounds[1]).contains(&x)
+            && (self.y_bounds[0]..=self.y_bounds[1]).contains(&y)
+    }
+
+    /// Fill the polygon with the given vertices (in canvas coordinates), using the even-odd rule.
+    fn fill_polygon(&mut self, points: &[(f64, f64)], color: Color) {
+        let Some(pixels) = points.iter()
+            .map(|&(x, y)| self.to_pixel(x, y))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+        if pixels.len() < 3 {
+            return;
+        }
+        let (res_w, res_h) = self.grid.resolution();
+        let mut crossings = Vec::new();
+        for py in 0..res_h {
+            let scan_y = py as f64;
+            crossings.clear();
+            for (i, &(x0, y0)) in pixels.iter().enumerate() {
+                let (x1, y1) = pixels[(i + 1) % pixels.len()];
+                if (y0 <= scan_y && scan_y < y1) || (y1 <= scan_y && scan_y < y0) {
+                    crossings.push(x0 + (scan_y - y0) / (y1 - y0) * (x1 - x0));
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                let start = span[0].round().max(0.0) as isize;
+                let end = span[1].round().min(res_w as f64 - 1.0) as isize;
+                for px in start..=end {
+                    self.paint(px, py as isize, color);
+                }
+            }
+        }
+    }
+}
+
+
+
+/// Something that can be drawn on a [`Canvas`].
+pub trait Shape {
+    /// Draw this shape with the given painter.
+    fn draw(&self, painter: &mut Painter);
+}
+
+/// A set of individual points.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Points<'a> {
+    pub coords: &'a [(f64, f64)],
+    pub color: Color,
+}
+
+impl Shape for Points<'_> {
+    fn draw(&self, painter: &mut Painter) {
+        for &(x, y) in self.coords {
+            painter.point(x, y, self.color);
+        }
+    }
+}
+
+/// A straight line between two points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Line {
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+    pub color: Color,
+}
+
+impl Shape for Line {
+    fn draw(&self, painter: &mut Painter) {
+        let (x_bounds, y_bounds) = painter.bounds();
+        let from = (self.x1, self.y1);
+        let to = (self.x2, self.y2);
+        let Some((from, to)) = clip_line(from, to, &x_bounds, &y_bounds) else {
+            return;
+        };
+        let (Some(from), Some(to)) = (
+            painter.to_pixel(from.0, from.1),
+            painter.to_pixel(to.0, to.1),
+        ) else {
+            return;
+        };
+        painter.paint_line(
+            (from.0.round() as isize, from.1.round() as isize),
+            (to.0.round() as isize, to.1.round() as isize),
+            self.color,
+        );
+    }
+}
+
+/// The outline of a circle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    pub color: Color,
+}
+
+impl Shape for Circle {
+    fn draw(&self, painter: &mut Painter) {
+        let Some((cx, cy)) = painter.to_pixel(self.x, self.y) else {
+            return;
+        };
+        // The radius (in pixels) along each axis, which differ when the canvas isn't square.
+        let (Some((rx, _)), Some((_, ry))) = (
+            painter.to_pixel(self.x + self.radius, self.y),
+            painter.to_pixel(self.x, self.y - self.radius),
+        ) else {
+            return;
+        };
+        let (rx, ry) = ((rx - cx).abs(), (ry - cy).abs());
+        let steps = ((rx.max(ry) * std::f64::consts::TAU).ceil() as usize).clamp(8, 4096);
+        let vertex = |i: usize| {
+            let theta = i as f64 / steps as f64 * std::f64::consts::TAU;
+            (
+                (cx + rx * theta.cos()).round() as isize,
+                (cy + ry * theta.sin()).round() as isize,
+            )
+        };
+        for i in 0..steps {
+            painter.paint_line(vertex(i), vertex(i + 1), self.color);
+        }
+    }
+}
+
+/// An axis-aligned rectangle, with its bottom-left corner at (`x`, `y`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub color: Color,
+    /// Whether to fill the rectangle, rather than only drawing its outline.
+    pub filled: bool,
+}
+
+impl Shape for Rect {
+    fn draw(&self, painter: &mut Painter) {
+        Polygon {
+            points: vec![
+                (self.x, self.y),
+                (self.x + self.width, self.y),
+                (self.x + self.width, self.y + self.height),
+                (self.x, self.y + self.height),
+            ],
+            color: self.color,
+            filled: self.filled,
+        }
+        .draw(painter);
+    }
+}
+
+/// A closed polygon, made from the given vertices.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polygon {
+    pub points: Vec<(f64, f64)>,
+    pub color: Color,
+    /// Whether to fill the polygon, rather than only drawing its outline.
+    pub filled: bool,
+}
+
+impl Shape for Polygon {
+    fn draw(&self, painter: &mut Painter) {
+        if self.filled {
+            painter.fill_polygon(&self.points, self.color);
+        }
+        for (i, &(x1, y1)) in self.points.iter().enumerate() {
+            let (x2, y2) = self.points[(i + 1) % self.points.len()];
+            Line { x1, y1, x2, y2, color: self.color }.draw(painter);
+        }
+    }
+}
+
+
+
+/// The symbol used to draw a pixel, which also determines the number of pixels per cell.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Marker {
+    /// Braille patterns, with 2x4 pixels per cell.
+    #[default]
+    Braille,
+    /// Half blocks (`▀`, `▄`), with 1x2 pixels per cell.
+    HalfBlock,
+    /// Quadrant blocks (`▘`, `▚`, `▙`, etc.), with 2x2 pixels per cell.
+    Quadrant,
+    /// A full block (`█`) per cell.
+    Block,
+    /// A single `•` per cell.
+    Dot,
+}
+
+impl Marker {
+    /// The number of horizontal and vertical pixels this marker has per cell.
+    pub const fn resolution(&self) -> (u16, u16) {
+        match self {
+            Marker::Braille => (2, 4),
+            Marker::HalfBlock => (1, 2),
+            Marker::Quadrant => (2, 2),
+            Marker::Block | Marker::Dot => (1, 1),
+        }
+    }
+
+    /// The symbol for a cell with all of its pixels painted.
+    pub const fn full_symbol(&self) -> char {
+        match self {
+            Marker::Braille => '⣿',
+            Marker::HalfBlock | Marker::Quadrant | Marker::Block => '█',
+            Marker::Dot => '•',
+        }
+    }
+
+    /// The symbol for a cell with the given painted pixels, where bit `row * cols + col` is set
+    /// for each painted pixel.
+    fn symbol(&self, pixels: u8) -> char {
+        match self {
+            Marker::Braille => {
+                let dots = (0..8)
+                    .filter(|i| pixels & (1 << i) != 0)
+                    .fold(0, |dots, i| dots | BRAILLE_DOTS[i / 2][i % 2]);
+                char::from_u32(0x2800 + dots as u32).unwrap_or(' ')
+            }
+            Marker::HalfBlock => [' ', '▀', '▄', '█'][pixels as usize & 0b11],
+            Marker::Quadrant => QUADRANTS[pixels as usize & 0b1111],
+            Marker::Block | Marker::Dot => self.full_symbol(),
+        }
+    }
+}
+
+/// The Braille dot bit for each `[row][column]` within a cell.
+const BRAILLE_DOTS: [[u8; 2]; 4] = [
+    [0x01, 0x08],
+    [0x02, 0x10],
+    [0x04, 0x20],
+    [0x40, 0x80],
+];
+
+/// Quadrant symbols, indexed by their painted pixels (top-left, top-right, bottom-left,
+/// bottom-right).
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+
+
+/// A grid of pixels, with each painted pixel remembering its color and when it was painted.
+struct Grid {
+    width: u16,
+    height: u16,
+    marker: Marker,
+    pixels: Vec<Option<(Color, u32)>>,
+    painted: u32,
+}
+
+impl Grid {
+    fn new(width: u16, height: u16, marker: Marker) -> Self {
+        let (rx, ry) = marker.resolution();
+        Self {
+            width,
+            height,
+            marker,
+            pixels: vec![None; width as usize * rx as usize * height as usize * ry as usize],
+            painted: 0,
+        }
+    }
+
+    fn resolution(&self) -> (usize, usize) {
+        let (rx, ry) = self.marker.resolution();
+        (self.width as usize * rx as usize, self.height as usize * ry as usize)
+    }
+
+    fn paint(&mut self, x: isize, y: isize, color: Color) {
+        let (res_w, res_h) = self.resolution();
+        if x < 0 || y < 0 || x as usize >= res_w || y as usize >= res_h {
+            return;
+        }
+        self.painted = self.painted.wrapping_add(1);
+        self.pixels[y as usize * res_w + x as usize] = Some((color, self.painted));
+    }
+
+    fn render(&self, area: Area, buf: &mut impl RenderTarget) {
+        let (rx, ry) = self.marker.resolution();
+        let (rx, ry) = (rx as usize, ry as usize);
+        let (res_w, _) = self.resolution();
+        // The painted pixels in the current cell, as (bit index, color, time painted).
+        let mut cell_pixels = Vec::with_capacity(rx * ry);
+
+        for cy in 0..self.height as usize {
+            for cx in 0..self.width as usize {
+                cell_pixels.clear();
+                for row in 0..ry {
+                    for col in 0..rx {
+                        let i = (cy * ry + row) * res_w + cx * rx + col;
+                        if let Some((color, time)) = self.pixels[i] {
+                            cell_pixels.push((row * rx + col, color, time));
+                        }
+                    }
+                }
+                if cell_pixels.is_empty() {
+                    continue;
+                }
+
+                let Some(cell) = buf.cell_mut(area.x + cx as u16, area.y + cy as u16) else {
+                    continue;
+                };
+                if self.marker == Marker::HalfBlock && cell_pixels.len() == 2
+                    && cell_pixels[0].1 != cell_pixels[1].1
+                {
+                    cell.set_char('▀').set_style((cell_pixels[0].1, cell_pixels[1].1));
+                    continue;
+                }
+
+                let bits = cell_pixels.iter().fold(0u8, |bits, (bit, _, _)| bits | (1 << bit));
+                cell.set_char(self.marker.symbol(bits))
+                    .set_style(dominant_color(&cell_pixels));
+            }
+        }
+    }
+}
+
+/// Get the color that covers the most pixels, preferring the most recently painted on ties.
+fn dominant_color(pixels: &[(usize, Color, u32)]) -> Color {
+    let mut best = (0, 0, Color::Reset);
+    for &(_, color, _) in pixels {
+        let (count, latest) = pixels.iter()
+            .filter(|(_, c, _)| *c == color)
+            .fold((0, 0), |(count, latest), (_, _, time)| (count + 1, latest.max(*time)));
+        if (count, latest) > (best.0, best.1) {
+            best = (count, latest, color);
+        }
+    }
+    best.2
+}
+
+/// Clip the line between `from` and `to` to the given bounds with the Liang-Barsky algorithm.
+pub(crate) fn clip_line(
+    from: (f64, f64),
+    to: (f64, f64),
+    x_bounds: &[f64; 2],
+    y_bounds: &[f64; 2],
+) -> Option<((f64, f64), (f64, f64))> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let mut t0: f64 = 0.0;
+    let mut t1: f64 = 1.0;
+    for (p, q) in [
+        (-dx, from.0 - x_bounds[0]),
+        (dx, x_bounds[1] - from.0),
+        (-dy, from.1 - y_bounds[0]),
+        (dy, y_bounds[1] - from.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+
+    Some((
+        (from.0 + t0 * dx, from.1 + t0 * dy),
+        (from.0 + t1 * dx, from.1 + t1 * dy),
+    ))
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{buffer, row};
+
+    #[test]
+    fn canvas_markers() {
+        let mut buf = buffer(2, 1);
+        Canvas::new([0.0, 1.0], [0.0, 1.0])
+            .with_marker(Marker::Quadrant)
+            .render(buf.area, &mut buf, |painter| {
+                painter.draw(&Line { x1: 0.0, y1: 1.0, x2: 1.0, y2: 0.0, color: Color::Red });
+            });
+        assert_eq!(row(&buf, 0), "▀▄");
+
+        let mut buf = buffer(2, 2);
+        Canvas::new([0.0, 1.0], [0.0, 1.0])
+            .with_marker(Marker::HalfBlock)
+            .render(buf.area, &mut buf, |painter| {
+                painter.draw(&Rect {
+                    x: 0.0, y: 0.0, width: 1.0, height: 1.0, color: Color::Red, filled: true,
+                });
+            });
+        assert_eq!(row(&buf, 0), "██");
+        assert_eq!(row(&buf, 1), "██");
+    }
+
+    #[test]
+    fn canvas_color_resolution() {
+        let mut buf = buffer(1, 1);
+        Canvas::new([0.0, 1.0], [0.0, 3.0])
+            .render(buf.area, &mut buf, |painter| {
+                // Three pixels of red, then one of blue.
+                painter.draw(&Line { x1: 0.0, y1: 0.0, x2: 0.0, y2: 2.0, color: Color::Red });
+                painter.point(1.0, 3.0, Color::Blue);
+            });
+        assert_eq!(buf.content[0].symbol(), "⡎");
+        assert_eq!(buf.content[0].fg, Color::Red);
+
+        let mut buf = buffer(1, 1);
+        Canvas::new([0.0, 1.0], [0.0, 1.0])
+            .with_marker(Marker::HalfBlock)
+            .render(buf.area, &mut buf, |painter| {
+                painter.point(0.0, 1.0, Color::Red);
+                painter.point(0.0, 0.0, Color::Blue);
+            });
+        assert_eq!(buf.content[0].symbol(), "▀");
+        assert_eq!((buf.content[0].fg, buf.content[0].bg), (Color::Red, Color::Blue));
+    }
+
+    #[test]
+    fn line_clipping() {
+        let clipped = clip_line((-1.0, 0.5), (2.0, 0.5), &[0.0, 1.0], &[0.0, 1.0]);
+        assert_eq!(clipped, Some(((0.0, 0.5), (1.0, 0.5))));
+
+        let outside = clip_line((-1.0, 2.0), (2.0, 2.0), &[0.0, 1.0], &[0.0, 1.0]);
+        assert_eq!(outside, None);
+    }
+}
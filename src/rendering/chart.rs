@@ -0,0 +1,510 @@
+//! Chart Widget
+
+
+
+use unicode_width::UnicodeWidthStr as _;
+
+use crate::{
+    Area, Canvas, Color, Line, Marker, Painter, Points, Rectangle, RectangleStyle, RenderTarget,
+    Style,
+};
+
+
+
+/// A line or scatter chart, drawn with X and Y axes.
+///
+/// Datasets are drawn on a [`Canvas`], so each [`Dataset`]'s resolution depends on its [`Marker`]
+/// (a [`Marker::Braille`] line has 2x4 "pixels" per cell).
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// let data = [(0.0, 1.0), (1.0, 3.0), (2.0, 2.0)];
+/// let chart = Chart::new(vec![Dataset::new("latency", &data).with_color(Color::Cyan)])
+///     .with_x_axis(Axis::new([0.0, 2.0]).with_title("time").with_ticks(3))
+///     .with_y_axis(Axis::new([0.0, 4.0]).with_ticks(2));
+///
+/// let mut buf = Buffer::empty();
+/// buf.resize(Area::new(0, 0, 20, 8));
+/// chart.render(buf.area, &mut buf);
+/// ```
+pub struct Chart<'a> {
+    /// The datasets to plot, drawn in order (later datasets are drawn on top).
+    pub datasets: Vec<Dataset<'a>>,
+    /// The horizontal axis.
+    pub x_axis: Axis,
+    /// The vertical axis.
+    pub y_axis: Axis,
+    /// Where to place the legend inside the plotting area, if anywhere.
+    pub legend: Option<LegendPosition>,
+    /// The style used for the legend's border.
+    pub legend_style: Style,
+}
+
+impl<'a> Chart<'a> {
+    /// Create a new chart for the given datasets, with unit bounds on both axes.
+    pub fn new(datasets: Vec<Dataset<'a>>) -> Self {
+        Self {
+            datasets,
+            x_axis: Axis::new([0.0, 1.0]),
+            y_axis: Axis::new([0.0, 1.0]),
+            legend: Some(LegendPosition::TopRight),
+            legend_style: Style::default(),
+        }
+    }
+
+    /// Set the horizontal axis for this chart.
+    pub fn with_x_axis(mut self, axis: Axis) -> Self {
+        self.x_axis = axis;
+        self
+    }
+
+    /// Set the vertical axis for this chart.
+    pub fn with_y_axis(mut self, axis: Axis) -> Self {
+        self.y_axis = axis;
+        self
+    }
+
+    /// Set the legend's position. Use `None` to hide the legend.
+    pub fn with_legend(mut self, legend: Option<LegendPosition>) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Render this chart's axes, datasets, and legend in the given area.
+    ///
+    /// The area is clipped to the buffer, and nothing is drawn if it's too small to fit the axes
+    /// and their labels.
+    pub fn render(self, area: Area, buf: &mut impl RenderTarget) {
+        let area = area.intersection(buf.area());
+        let Some(layout) = self.layout(area) else {
+            return;
+        };
+
+        self.render_axes(area, &layout, buf);
+
+        // Datasets that share a marker share a canvas, so that overlapping cells get resolved to
+        // a single color.
+        let mut markers = Vec::new();
+        for dataset in &self.datasets {
+            if !markers.contains(&dataset.marker) {
+                markers.push(dataset.marker);
+            }
+        }
+        for marker in markers {
+            Canvas::new(self.x_axis.bounds, self.y_axis.bounds)
+                .with_marker(marker)
+                .render(layout.graph, buf, |painter| {
+                    for dataset in self.datasets.iter().filter(|d| d.marker == marker) {
+                        dataset.draw(painter);
+                    }
+                });
+        }
+
+        if let Some(position) = self.legend {
+            self.render_legend(layout.graph, position, buf);
+        }
+    }
+}
+
+struct ChartLayout {
+    /// The area that the datasets are plotted in.
+    graph: Area,
+    /// The column the Y axis line is drawn in.
+    y_axis_x: u16,
+    /// The row the X axis line is drawn in.
+    x_axis_y: u16,
+    /// The row the X axis labels are drawn in, if there are any.
+    x_labels_y: Option<u16>,
+    /// The row the X axis title is drawn in, below the labels, if there is one.
+    x_title_y: Option<u16>,
+}
+
+impl Chart<'_> {
+    fn layout(&self, area: Area) -> Option<ChartLayout> {
+        let y_labels_w = self.y_axis.labels.iter()
+            .map(|label| label.width() as u16)
+            .max()
+            .unwrap_or(0);
+
+        let top = area.top() + u16::from(self.y_axis.title.is_some());
+        let mut bottom = area.bottom();
+        let x_title_y = if self.x_axis.title.is_none() {
+            None
+        } else {
+            bottom = bottom.checked_sub(1)?;
+            Some(bottom)
+        };
+        let x_labels_y = if self.x_axis.labels.is_empty() {
+            None
+        } else {
+            bottom = bottom.checked_sub(1)?;
+            Some(bottom)
+        };
+        let x_axis_y = bottom.checked_sub(1)?;
+        let y_axis_x = area.left() + y_labels_w;
+
+        let graph = Area::new(
+            y_axis_x + 1,
+            top,
+            area.right().checked_sub(y_axis_x + 1)?,
+            x_axis_y.checked_sub(top)?,
+        );
+        if graph.is_empty() {
+            return None;
+        }
+
+        Some(ChartLayout { graph, y_axis_x, x_axis_y, x_labels_y, x_title_y })
+    }
+
+    fn render_axes(&self, area: Area, layout: &ChartLayout, buf: &mut impl RenderTarget) {
+        let graph = layout.graph;
+
+        for y in graph.top()..layout.x_axis_y {
+            if let Some(cell) = buf.cell_mut(layout.y_axis_x, y) {
+                cell.set_char('│').set_style(self.y_axis.style);
+            }
+        }
+        for x in graph.left()..graph.right() {
+            if let Some(cell) = buf.cell_mut(x, layout.x_axis_y) {
+                cell.set_char('─').set_style(self.x_axis.style);
+            }
+        }
+        if let Some(cell) = buf.cell_mut(layout.y_axis_x, layout.x_axis_y) {
+            cell.set_char('└').set_style(self.x_axis.style);
+        }
+
+        if let Some(title) = &self.y_axis.title {
+            buf.set_stringn(
+                layout.y_axis_x,
+                area.y,
+                title,
+                area.right().saturating_sub(layout.y_axis_x) as usize,
+                self.y_axis.style,
+            );
+        }
+        if let (Some(title), Some(y)) = (&self.x_axis.title, layout.x_title_y) {
+            let w = (title.width() as u16).min(graph.w);
+            buf.set_stringn(
+                graph.right() - w,
+                y,
+                title,
+                w as usize,
+                self.x_axis.style,
+            );
+        }
+
+        // Y axis labels are right-aligned against the axis, from the bottom up.
+        let count = self.y_axis.labels.len();
+        for (i, label) in self.y_axis.labels.iter().enumerate() {
+            let offset = if count > 1 {
+                (i as u32 * (graph.h as u32 - 1) / (count as u32 - 1)) as u16
+            } else {
+                0
+            };
+            let w = label.width() as u16;
+            buf.set_stringn(
+                layout.y_axis_x - w,
+                graph.bottom() - 1 - offset,
+                label,
+                w as usize,
+                self.y_axis.style,
+            );
+        }
+
+        // X axis labels are spread across the graph, with the first and last labels aligned to
+        // its edges.
+        if let Some(y) = layout.x_labels_y {
+            let count = self.x_axis.labels.len();
+            for (i, label) in self.x_axis.labels.iter().enumerate() {
+                let w = label.width() as u16;
+                let x = if count == 1 || i == 0 {
+                    graph.x
+                } else {
+                    let tick = graph.x
+                        + (i as u32 * (graph.w as u32 - 1) / (count as u32 - 1)) as u16;
+                    if i == count - 1 {
+                        (tick + 1).saturating_sub(w)
+                    } else {
+                        tick.saturating_sub(w / 2)
+                    }
+                }
+                .max(area.x);
+                buf.set_stringn(
+                    x,
+                    y,
+                    label,
+                    area.right().saturating_sub(x) as usize,
+                    self.x_axis.style,
+                );
+            }
+        }
+    }
+
+    fn render_legend(
+        &self,
+        graph: Area,
+        position: LegendPosition,
+        buf: &mut impl RenderTarget,
+    ) {
+        let entries = self.datasets.iter()
+            .filter(|dataset| !dataset.name.is_empty())
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return;
+        }
+        let inner_w = entries.iter()
+            .map(|dataset| dataset.name.width() as u16 + 2)
+            .max()
+            .unwrap_or(0);
+        let w = inner_w + 2;
+        let h = entries.len() as u16 + 2;
+        if w > graph.w || h > graph.h {
+            return;
+        }
+
+        let x = match position {
+            LegendPosition::TopLeft | LegendPosition::BottomLeft => graph.left(),
+            LegendPosition::TopRight | LegendPosition::BottomRight => graph.right() - w,
+        };
+        let y = match position {
+            LegendPosition::TopLeft | LegendPosition::TopRight => graph.top(),
+            LegendPosition::BottomLeft | LegendPosition::BottomRight => graph.bottom() - h,
+        };
+        let legend_area = Area::new(x, y, w, h);
+
+        for row in legend_area.rows() {
+            for x in row.left()..row.right() {
+                if let Some(cell) = buf.cell_mut(x, row.y) {
+                    cell.reset();
+                }
+            }
+        }
+        Rectangle {
+            style: self.legend_style,
+            rect_style: RectangleStyle::Normal,
+        }
+        .render(legend_area, buf);
+
+        for (i, dataset) in entries.into_iter().enumerate() {
+            let y = legend_area.y + 1 + i as u16;
+            if let Some(cell) = buf.cell_mut(legend_area.x + 1, y) {
+                cell.set_char(dataset.marker.full_symbol())
+                    .set_style(Style::default().fg(dataset.color));
+            }
+            buf.set_stringn(
+                legend_area.x + 3,
+                y,
+                &dataset.name,
+                inner_w as usize - 2,
+                Style::default(),
+            );
+        }
+    }
+}
+
+
+
+/// A [`Chart`] axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Axis {
+    /// The title drawn next to this axis.
+    pub title: Option<String>,
+    /// The lower and upper bounds of this axis. Data outside of these bounds is not drawn.
+    pub bounds: [f64; 2],
+    /// The tick labels for this axis, from the lower bound to the upper bound.
+    pub labels: Vec<String>,
+    /// The style used for this axis' line, title, and labels.
+    pub style: Style,
+}
+
+impl Axis {
+    /// Create a new axis with the given bounds, and no title or labels.
+    pub fn new(bounds: [f64; 2]) -> Self {
+        Self {
+            title: None,
+            bounds,
+            labels: Vec::new(),
+            style: Style::default(),
+        }
+    }
+
+    /// Set the title for this axis.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the tick labels for this axis.
+    pub fn with_labels<I, S>(mut self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.labels = labels.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Generate `count` evenly spaced tick labels from this axis' bounds.
+    pub fn with_ticks(mut self, count: usize) -> Self {
+        let [min, max] = self.bounds;
+        let step = if count > 1 { (max - min) / (count - 1) as f64 } else { 0.0 };
+        // Only show decimals when the ticks don't fall on whole numbers.
+        let precision = if step.fract() == 0.0 && min.fract() == 0.0 { 0 } else { 2 };
+        self.labels = (0..count)
+            .map(|i| format!("{:.*}", precision, min + step * i as f64))
+            .collect();
+        self
+    }
+
+    /// Set the style for this axis.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+
+
+/// A set of points to be plotted in a [`Chart`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dataset<'a> {
+    /// The name shown in the chart's legend. Datasets with empty names are left out of it.
+    pub name: String,
+    /// The `(x, y)` points in this dataset.
+    pub data: &'a [(f64, f64)],
+    /// The color this dataset is drawn with.
+    pub color: Color,
+    /// The marker this dataset is drawn with.
+    pub marker: Marker,
+    /// How this dataset's points are drawn.
+    pub graph_type: GraphType,
+}
+
+impl<'a> Dataset<'a> {
+    /// Create a new dataset, drawn as a line with [`Marker::Braille`].
+    pub fn new(name: impl Into<String>, data: &'a [(f64, f64)]) -> Self {
+        Self {
+            name: name.into(),
+            data,
+            color: Color::Reset,
+            marker: Marker::Braille,
+            graph_type: GraphType::Line,
+        }
+    }
+
+    /// Set the color for this dataset.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set the marker for this dataset.
+    pub fn with_marker(mut self, marker: Marker) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    /// Set the graph type for this dataset.
+    pub fn with_graph_type(mut self, graph_type: GraphType) -> Self {
+        self.graph_type = graph_type;
+        self
+    }
+
+    fn draw(&self, painter: &mut Painter) {
+        match self.graph_type {
+            GraphType::Scatter => {
+                painter.draw(&Points { coords: self.data, color: self.color });
+            }
+            GraphType::Line => {
+                if let [(x, y)] = self.data {
+                    painter.point(*x, *y, self.color);
+                }
+                for pair in self.data.windows(2) {
+                    painter.draw(&Line {
+                        x1: pair[0].0,
+                        y1: pair[0].1,
+                        x2: pair[1].0,
+                        y2: pair[1].1,
+                        color: self.color,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// How a [`Dataset`] is drawn in a [`Chart`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum GraphType {
+    /// Draw each point on its own.
+    Scatter,
+    /// Draw lines between consecutive points.
+    #[default]
+    Line,
+}
+
+/// Where a [`Chart`]'s legend is placed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LegendPosition {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{buffer, row};
+
+    #[test]
+    fn chart_axes_and_labels() {
+        let mut buf = buffer(10, 5);
+        Chart::new(vec![])
+            .with_x_axis(Axis::new([0.0, 1.0]).with_labels(["0", "1"]))
+            .with_y_axis(Axis::new([0.0, 10.0]).with_labels(["0", "10"]))
+            .render(buf.area, &mut buf);
+
+        assert_eq!(row(&buf, 0), "10│       ");
+        assert_eq!(row(&buf, 2), " 0│       ");
+        assert_eq!(row(&buf, 3), "  └───────");
+        assert_eq!(row(&buf, 4), "   0     1");
+    }
+
+    #[test]
+    fn chart_axis_titles() {
+        let data = [(0.0, 0.0), (1.0, 0.0)];
+        let mut buf = buffer(10, 5);
+        Chart::new(vec![Dataset::new("", &data)])
+            .with_x_axis(Axis::new([0.0, 1.0]).with_title("x").with_labels(["0", "1"]))
+            .with_y_axis(Axis::new([0.0, 1.0]).with_title("y"))
+            .render(buf.area, &mut buf);
+
+        // The X axis title gets its own row below the labels, so it doesn't cover the data.
+        assert_eq!(row(&buf, 0), "y         ");
+        assert_eq!(row(&buf, 1), "│⣀⣀⣀⣀⣀⣀⣀⣀⣀");
+        assert_eq!(row(&buf, 2), "└─────────");
+        assert_eq!(row(&buf, 3), " 0       1");
+        assert_eq!(row(&buf, 4), "         x");
+    }
+
+    #[test]
+    fn chart_braille_line() {
+        let data = [(0.0, 0.0), (1.0, 0.0)];
+        let mut buf = buffer(4, 2);
+        Chart::new(vec![Dataset::new("", &data)])
+            .with_x_axis(Axis::new([0.0, 1.0]))
+            .with_y_axis(Axis::new([0.0, 1.0]))
+            .render(buf.area, &mut buf);
+
+        // The bottom row of dots in every cell of the (single-row) graph.
+        assert_eq!(row(&buf, 0), "│⣀⣀⣀");
+        assert_eq!(row(&buf, 1), "└───");
+    }
+}
@@ -0,0 +1,43 @@
+//! Clearing Widget
+
+
+
+use crate::{Area, RenderTarget};
+
+
+
+/// Resets every [`Cell`](crate::Cell) in an area.
+///
+/// This is mostly useful on a [`Layer`](crate::Layer), where rendering a `Clear` before a popup
+/// stops whatever is below the popup from showing through it.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// fn render_dialog(frame: &mut Frame) {
+///     let area = frame.area().inner_centered(30, 8);
+///     let layer = frame.layer("dialog", 1);
+///     layer.dim_below().shadow(area);
+///
+///     Clear.render(area, &mut layer.buffer);
+///     Rectangle::new(Style::default()).render(area, &mut layer.buffer);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Clear;
+
+impl Clear {
+    /// Reset every cell in the given area, clipped to the buffer.
+    pub fn render(self, area: Area, buf: &mut impl RenderTarget) {
+        let area = area.intersection(buf.area());
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                if let Some(cell) = buf.cell_mut(x, y) {
+                    cell.reset();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! Gradients & Color Effects
+
+
+
+use crate::{Area, Cell, Color, RenderTarget, Style};
+
+
+
+/// A smooth transition between colors, used to fill an area or color a piece of text.
+///
+/// Gradients are made of color stops, positioned from `0.0` (the start of the gradient) to `1.0`
+/// (the end of it). Colors between two stops are interpolated in the gradient's [`ColorSpace`].
+///
+/// Terminal cells are roughly twice as tall as they are wide, so gradients are laid out as if
+/// each row were two columns tall. This keeps diagonal gradients at the angle they were given, and
+/// radial gradients round.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// fn render_banner(frame: &mut Frame) {
+///     let area = frame.area();
+///     Gradient::new(Color::Rgb(40, 20, 80), Color::Rgb(20, 80, 120))
+///         .linear(45.0)
+///         .with_space(ColorSpace::Oklab)
+///         .render(area, frame.buffer);
+///     Gradient::new(Color::Rgb(255, 200, 0), Color::Rgb(255, 0, 120))
+///         .with_target(ColorTarget::Fg)
+///         .render_text(2, 1, "Welcome!", Style::new().bold(), frame.buffer);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    /// The gradient's color stops, as positions (from `0.0` to `1.0`) and colors, in order.
+    pub stops: Vec<(f64, Color)>,
+    /// How the gradient is laid out over an area.
+    pub shape: GradientShape,
+    /// The color space that colors are interpolated in.
+    pub space: ColorSpace,
+    /// Which of each cell's colors the gradient is applied to.
+    pub target: ColorTarget,
+}
+
+/// How a [`Gradient`] is laid out over an area.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientShape {
+    /// A gradient along a line, at the given angle in degrees. `0.0` goes from left to right, and
+    /// `90.0` goes from top to bottom.
+    Linear { angle: f64 },
+    /// A gradient that starts at the center of the area, and ends at its corners.
+    Radial,
+}
+
+/// The color space that colors are interpolated in.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ColorSpace {
+    /// Interpolate each of the red, green, and blue channels separately. This is the cheapest
+    /// option, but transitions between very different colors can look muddy in the middle.
+    #[default]
+    Rgb,
+    /// Interpolate in the Oklab color space, where equal steps look like equal changes in color.
+    Oklab,
+}
+
+/// Which of a [`Cell`]'s colors an effect is applied to.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ColorTarget {
+    /// The foreground (text) color.
+    Fg,
+    /// The background color.
+    #[default]
+    Bg,
+    /// Both the foreground and background colors.
+    Both,
+}
+
+impl Gradient {
+    /// Create a new horizontal gradient between two colors, applied to the background.
+    pub fn new(from: Color, to: Color) -> Self {
+        Self::from_stops([(0.0, from), (1.0, to)])
+    }
+
+    /// Create a new horizontal gradient with the given color stops, applied to the background.
+    ///
+    /// The stops are sorted by their positions, which are clamped between `0.0` and `1.0`.
+    pub fn from_stops(stops: impl IntoIterator<Item = (f64, Color)>) -> Self {
+        let mut stops = stops
+            .into_iter()
+            .map(|(position, color)| (position.clamp(0.0, 1.0), color))
+            .collect::<Vec<_>>();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self {
+            stops,
+            shape: GradientShape::Linear { angle: 0.0 },
+            space: ColorSpace::default(),
+            target: ColorTarget::default(),
+        }
+    }
+
+    /// Add a color stop at the given position (from `0.0` to `1.0`).
+    pub fn with_stop(mut self, position: f64, color: Color) -> Self {
+        let position = position.clamp(0.0, 1.0);
+        let index = self.stops.partition_point(|(p, _)| *p <= position);
+        self.stops.insert(index, (position, color));
+        self
+    }
+
+    /// Lay this gradient out along a line at the given angle, in degrees.
+    pub fn linear(mut self, angle: f64) -> Self {
+        self.shape = GradientShape::Linear { angle };
+        self
+    }
+
+    /// Lay this gradient out from the center of the area to its corners.
+    pub fn radial(mut self) -> Self {
+        self.shape = GradientShape::Radial;
+        self
+    }
+
+    /// Set the color space that colors are interpolated in.
+    pub fn with_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Set which of each cell's colors the gradient is applied to.
+    pub fn with_target(mut self, target: ColorTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Get the color at the given position (from `0.0` to `1.0`) along this gradient.
+    ///
+    /// Positions before the first stop or after the last one get that stop's color. A gradient
+    /// without any stops is [`Color::Reset`] everywhere.
+    pub fn at(&self, position: f64) -> Color {
+        let Some(&(first_position, first)) = self.stops.first() else {
+            return Color::Reset;
+        };
+        if position <= first_position {
+            return first;
+        }
+        for pair in self.stops.windows(2) {
+            let ((start, from), (end, to)) = (pair[0], pair[1]);
+            if position <= end {
+                let t = if end > start { (position - start) / (end - start) } else { 1.0 };
+                return self.space.interpolate(from, to, t);
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+
+    /// Fill the given area with this gradient, leaving each cell's symbol as it is.
+    pub fn render(self, area: Area, buf: &mut impl RenderTarget) {
+        let layout = Layout::new(self.shape, area);
+        let area = area.intersection(buf.area());
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let color = self.at(layout.position(x, y));
+                if let Some(cell) = buf.cell_mut(x, y) {
+                    self.target.apply(cell, |_| color);
+                }
+            }
+        }
+    }
+
+    /// Write a string to the buffer, starting at the position (x, y), with this gradient spread
+    /// across its characters from the first to the last.
+    ///
+    /// The gradient's shape is ignored, since text only runs in one direction.
+    pub fn render_text<T, S>(
+        self,
+        x: u16,
+        y: u16,
+        string: T,
+        style: S,
+        buf: &mut impl RenderTarget,
+    ) -> (u16, u16)
+    where
+        T: AsRef<str>,
+        S: Into<Style>,
+    {
+        let (end_x, _) = buf.set_stringn(x, y, string, usize::MAX, style);
+        let last = end_x.saturating_sub(x).saturating_sub(1).max(1) as f64;
+        for cx in x..end_x {
+            let color = self.at((cx - x) as f64 / last);
+            if let Some(cell) = buf.cell_mut(cx, y) {
+                self.target.apply(cell, |_| color);
+            }
+        }
+        (end_x, y)
+    }
+}
+
+/// Maps cell positions within an area to positions along a [`Gradient`].
+struct Layout {
+    shape: GradientShape,
+    area: Area,
+    /// The direction of a linear gradient.
+    direction: (f64, f64),
+    /// The range of distances covered by the gradient: along its direction for linear gradients,
+    /// or from the center for radial ones.
+    min: f64,
+    max: f64,
+}
+
+impl Layout {
+    fn new(shape: GradientShape, area: Area) -> Self {
+        let (w, h) = (area.w as f64, area.h as f64 * 2.0);
+        match shape {
+            GradientShape::Linear { angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let projections = [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)]
+                    .map(|(x, y)| x * cos + y * sin);
+                Self {
+                    shape,
+                    area,
+                    direction: (cos, sin),
+                    min: projections.into_iter().fold(f64::INFINITY, f64::min),
+                    max: projections.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                }
+            }
+            GradientShape::Radial => Self {
+                shape,
+                area,
+                direction: (0.0, 0.0),
+                min: 0.0,
+                max: (w * w + h * h).sqrt() / 2.0,
+            },
+        }
+    }
+
+    /// The position along the gradient of the center of the cell at (x, y).
+    fn position(&self, x: u16, y: u16) -> f64 {
+        let px = (x - self.area.x) as f64 + 0.5;
+        let py = ((y - self.area.y) as f64 + 0.5) * 2.0;
+        let distance = match self.shape {
+            GradientShape::Linear { .. } => px * self.direction.0 + py * self.direction.1,
+            GradientShape::Radial => {
+                let dx = px - self.area.w as f64 / 2.0;
+                let dy = py - self.area.h as f64;
+                (dx * dx + dy * dy).sqrt()
+            }
+        };
+        if self.max > self.min {
+            ((distance - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+
+
+/// Moves the colors of every cell in an area toward a target color.
+///
+/// This is useful for transitions, and for drawing attention away from part of the screen.
+/// [`Color::Reset`] can't be blended, so cells with reset colors switch to the target color once
+/// the amount passes `0.5`.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// fn render_inactive_pane(frame: &mut Frame, area: Area) {
+///     Fade::new(Color::Black, 0.6).render(area, frame.buffer);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fade {
+    /// The color that cells fade toward.
+    pub color: Color,
+    /// How far to fade, from `0.0` (not at all) to `1.0` (all the way to the target color).
+    pub amount: f64,
+    /// The color space that colors are interpolated in.
+    pub space: ColorSpace,
+    /// Which of each cell's colors are faded.
+    pub target: ColorTarget,
+}
+
+impl Fade {
+    /// Create a new fade of both colors toward the given color, by the given amount.
+    pub fn new(color: Color, amount: f64) -> Self {
+        Self {
+            color,
+            amount,
+            space: ColorSpace::default(),
+            target: ColorTarget::Both,
+        }
+    }
+
+    /// Set the color space that colors are interpolated in.
+    pub fn with_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Set which of each cell's colors are faded.
+    pub fn with_target(mut self, target: ColorTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Fade the colors of every cell in the given area, leaving each cell's symbol as it is.
+    pub fn render(self, area: Area, buf: &mut impl RenderTarget) {
+        let area = area.intersection(buf.area());
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                if let Some(cell) = buf.cell_mut(x, y) {
+                    self.target.apply(cell, |color| {
+                        self.space.interpolate(color, self.color, self.amount)
+                    });
+                }
+            }
+        }
+    }
+}
+
+
+
+impl ColorSpace {
+    /// Interpolate between two colors, where `t` goes from `0.0` (`from`) to `1.0` (`to`).
+    ///
+    /// [`Color::Reset`] can't be interpolated, so if either color is reset, the closer of the two
+    /// is returned.
+    pub fn interpolate(self, from: Color, to: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Rgb => from.blend(to, t),
+            Self::Oklab => {
+                let (Some(a), Some(b)) = (from.to_rgb(), to.to_rgb()) else {
+                    return if t < 0.5 { from } else { to };
+                };
+                let (a, b) = (rgb_to_oklab(a), rgb_to_oklab(b));
+                let mix = |a: f64, b: f64| a + (b - a) * t;
+                oklab_to_rgb((mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2)))
+            }
+        }
+    }
+}
+
+impl ColorTarget {
+    fn apply(self, cell: &mut Cell, mut f: impl FnMut(Color) -> Color) {
+        if matches!(self, Self::Fg | Self::Both) {
+            cell.fg = f(cell.fg);
+        }
+        if matches!(self, Self::Bg | Self::Both) {
+            cell.bg = f(cell.bg);
+        }
+    }
+}
+
+/// Convert an sRGB color to Oklab (see <https://bottosson.github.io/posts/oklab/>).
+fn rgb_to_oklab((r, g, b): (u8, u8, u8)) -> (f64, f64, f64) {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (linear(r), linear(g), linear(b));
+
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    (
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    )
+}
+
+/// Convert an Oklab color back to sRGB, clamping it to the sRGB gamut.
+fn oklab_to_rgb((l, a, b): (f64, f64, f64)) -> Color {
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+    let r = 4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_;
+    let g = -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_;
+    let b = -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_;
+
+    let encode = |c: f64| {
+        let c = c.clamp(0.0, 1.0);
+        let c = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+        (c * 255.0).round() as u8
+    };
+    Color::Rgb(encode(r), encode(g), encode(b))
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Buffer;
+    use crate::test_utils::text;
+
+    const BLACK: Color = Color::Rgb(0, 0, 0);
+    const WHITE: Color = Color::Rgb(255, 255, 255);
+
+    #[test]
+    fn gradient_stops() {
+        let gradient = Gradient::new(BLACK, WHITE).with_stop(0.5, Color::Rgb(255, 0, 0));
+        assert_eq!(gradient.at(-1.0), BLACK);
+        assert_eq!(gradient.at(0.25), Color::Rgb(128, 0, 0));
+        assert_eq!(gradient.at(0.5), Color::Rgb(255, 0, 0));
+        assert_eq!(gradient.at(0.75), Color::Rgb(255, 128, 128));
+        assert_eq!(gradient.at(2.0), WHITE);
+        assert_eq!(Gradient::from_stops([]).at(0.5), Color::Reset);
+
+        assert_eq!(ColorSpace::Oklab.interpolate(BLACK, WHITE, 0.0), BLACK);
+        assert_eq!(ColorSpace::Oklab.interpolate(BLACK, WHITE, 1.0), WHITE);
+        // Halfway between black and white in lightness, rather than in each channel.
+        assert_eq!(ColorSpace::Oklab.interpolate(BLACK, WHITE, 0.5), Color::Rgb(99, 99, 99));
+    }
+
+    #[test]
+    fn gradient_fills() {
+        let bgs = |buf: &Buffer| buf.content.iter().map(|cell| cell.bg).collect::<Vec<_>>();
+
+        let mut buf = Buffer::new(Area::new(0, 0, 4, 2));
+        Gradient::new(BLACK, WHITE).render(Area::new(1, 0, 2, 1), &mut buf);
+        let (dark, light) = (Color::Rgb(64, 64, 64), Color::Rgb(191, 191, 191));
+        assert_eq!(bgs(&buf)[..4], [Color::Reset, dark, light, Color::Reset]);
+
+        let mut buf = Buffer::new(Area::new(0, 0, 1, 2));
+        Gradient::new(BLACK, WHITE).linear(90.0).render(buf.area, &mut buf);
+        assert_eq!(bgs(&buf), [dark, light]);
+
+        let mut buf = Buffer::new(Area::new(0, 0, 3, 1));
+        Gradient::new(BLACK, WHITE).radial().render(buf.area, &mut buf);
+        let bg = bgs(&buf);
+        assert_eq!(bg[0], bg[2]);
+        assert!(bg[1].luminance() < bg[0].luminance());
+
+        let mut buf = Buffer::new(Area::new(0, 0, 4, 1));
+        let end = Gradient::new(BLACK, WHITE)
+            .with_target(ColorTarget::Fg)
+            .render_text(0, 0, "abc", Style::new(), &mut buf);
+        assert_eq!(end, (3, 0));
+        let fgs = buf.content.iter().map(|cell| cell.fg).collect::<Vec<_>>();
+        assert_eq!(fgs, [BLACK, Color::Rgb(128, 128, 128), WHITE, Color::Reset]);
+    }
+
+    #[test]
+    fn fading() {
+        let mut buf = Buffer::new(Area::new(0, 0, 2, 1));
+        buf.set_string(0, 0, "ab", Style::new().fg(WHITE).bg(Color::Rgb(100, 100, 100)));
+        Fade::new(BLACK, 0.5).render(Area::new(1, 0, 1, 1), &mut buf);
+        assert_eq!((buf.content[0].fg, buf.content[0].bg), (WHITE, Color::Rgb(100, 100, 100)));
+        let faded = (Color::Rgb(128, 128, 128), Color::Rgb(50, 50, 50));
+        assert_eq!((buf.content[1].fg, buf.content[1].bg), faded);
+        assert_eq!(text(&buf), "ab");
+    }
+}
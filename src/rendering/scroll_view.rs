@@ -0,0 +1,146 @@
+//! Scrollable Viewport
+
+
+
+use crate::{Area, Buffer, RenderTarget, ScrollbarState};
+
+
+
+/// A viewport onto content that may be larger than the area it's displayed in.
+///
+/// The content is rendered into the scroll view's own [`Buffer`], which can be any size, and the
+/// visible portion of it is copied into the target buffer when the scroll view is rendered.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// let mut view = ScrollView::new(40, 100);
+/// for i in 0..100 {
+///     view.buffer_mut().set_string(0, i, format!("line {i}"), Style::default());
+/// }
+/// view.scroll_by(0, 20);
+///
+/// let mut buf = Buffer::new(Area::new(0, 0, 20, 10));
+/// view.render(buf.area, &mut buf);
+/// ```
+pub struct ScrollView {
+    buffer: Buffer,
+    /// The horizontal and vertical position of the viewport within the content.
+    pub offset: (u16, u16),
+}
+
+impl ScrollView {
+    /// Create a new scroll view for content of the given size.
+    pub fn new(content_w: u16, content_h: u16) -> Self {
+        Self {
+            buffer: Buffer::new(Area::new(0, 0, content_w, content_h)),
+            offset: (0, 0),
+        }
+    }
+
+    /// The width and height of the content.
+    pub fn content_size(&self) -> (u16, u16) {
+        (self.buffer.area.w, self.buffer.area.h)
+    }
+
+    /// Resize the content. Cells within both the old and new sizes are kept.
+    pub fn resize(&mut self, content_w: u16, content_h: u16) {
+        let area = Area::new(0, 0, content_w, content_h);
+        let mut buffer = Buffer::new(area);
+        buffer.blit(0, 0, &self.buffer, area);
+        self.buffer = buffer;
+    }
+
+    /// Get the content buffer, which uses its own coordinates (starting at (0, 0)).
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Get the content buffer, which uses its own coordinates (starting at (0, 0)), to render the
+    /// content into.
+    pub fn buffer_mut(&mut self) -> &mut Buffer {
+        &mut self.buffer
+    }
+
+    /// Move the viewport by the given amounts, without passing the edges of the content.
+    pub fn scroll_by(&mut self, dx: i32, dy: i32) {
+        let (w, h) = self.content_size();
+        self.offset = (
+            (self.offset.0 as i32 + dx).clamp(0, w.saturating_sub(1) as i32) as u16,
+            (self.offset.1 as i32 + dy).clamp(0, h.saturating_sub(1) as i32) as u16,
+        );
+    }
+
+    /// Scroll so that the given content position is visible in a viewport of the given size.
+    pub fn scroll_to_visible(&mut self, x: u16, y: u16, viewport_w: u16, viewport_h: u16) {
+        if x < self.offset.0 {
+            self.offset.0 = x;
+        } else if x >= self.offset.0.saturating_add(viewport_w) {
+            self.offset.0 = (x + 1).saturating_sub(viewport_w);
+        }
+        if y < self.offset.1 {
+            self.offset.1 = y;
+        } else if y >= self.offset.1.saturating_add(viewport_h) {
+            self.offset.1 = (y + 1).saturating_sub(viewport_h);
+        }
+    }
+
+    /// The largest offset that still fills a viewport of the given size with content.
+    pub fn max_offset(&self, viewport_w: u16, viewport_h: u16) -> (u16, u16) {
+        let (w, h) = self.content_size();
+        (w.saturating_sub(viewport_w), h.saturating_sub(viewport_h))
+    }
+
+    /// The vertical [`ScrollbarState`] for this view, when displayed with the given height.
+    pub fn vertical_scrollbar_state(&self, viewport_h: u16) -> ScrollbarState {
+        let mut state = ScrollbarState::new(self.buffer.area.h as usize, viewport_h as usize);
+        state.offset = self.offset.1 as usize;
+        state
+    }
+
+    /// The horizontal [`ScrollbarState`] for this view, when displayed with the given width.
+    pub fn horizontal_scrollbar_state(&self, viewport_w: u16) -> ScrollbarState {
+        let mut state = ScrollbarState::new(self.buffer.area.w as usize, viewport_w as usize);
+        state.offset = self.offset.0 as usize;
+        state
+    }
+
+    /// Copy the visible portion of the content into the given area of the buffer.
+    ///
+    /// The offset is first limited so that the viewport doesn't go past the end of the content.
+    pub fn render(&mut self, area: Area, buf: &mut impl RenderTarget) {
+        let (max_x, max_y) = self.max_offset(area.w, area.h);
+        self.offset = (self.offset.0.min(max_x), self.offset.1.min(max_y));
+
+        let window = Area::new(self.offset.0, self.offset.1, area.w, area.h);
+        buf.blit(area.x, area.y, &self.buffer, window);
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::row;
+
+    #[test]
+    fn scroll_view_window() {
+        let mut view = ScrollView::new(6, 3);
+        view.buffer_mut().set_string(0, 0, "abcdef", crate::Style::default());
+        view.buffer_mut().set_string(0, 2, "ab界ef", crate::Style::default());
+
+        let mut buf = Buffer::new(Area::new(10, 10, 3, 2));
+        view.offset = (1, 0);
+        view.render(buf.area, &mut buf);
+        assert_eq!(row(&buf, 10), "bcd");
+
+        // Wide graphemes that get cut off by the edge of the viewport are cleared.
+        view.offset = (0, 2);
+        view.render(buf.area, &mut buf);
+        assert_eq!(view.offset, (0, 1));
+        assert_eq!(row(&buf, 11), "ab ");
+    }
+}
@@ -0,0 +1,393 @@
+//! Scrollbar Widget
+
+
+
+use crate::{Area, InputContext, MouseButton, RenderTarget, Style};
+
+
+
+/// A vertical or horizontal scrollbar.
+///
+/// The thumb is positioned with half-cell precision, and its size reflects the portion of the
+/// content that is visible in the viewport. See [`ScrollbarState`] for the scrolling state that a
+/// scrollbar displays and updates.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// fn render_log(frame: &mut Frame, input: &InputContext, state: &mut ScrollbarState) {
+///     let (log_area, _) = frame.area().hsplit_len(frame.area().w.saturating_sub(1));
+///     let scrollbar = Scrollbar::vertical();
+///     let bar_area = scrollbar.beside(log_area);
+///
+///     scrollbar.handle_input(bar_area, state, input);
+///     scrollbar.render(bar_area, frame.buffer, state);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Scrollbar {
+    /// The direction this scrollbar scrolls in.
+    pub orientation: ScrollbarOrientation,
+    /// The symbols used to draw this scrollbar.
+    pub symbols: ScrollbarSymbols,
+    /// The style of the track (the part of the scrollbar not covered by the thumb).
+    pub track_style: Style,
+    /// The style of the thumb.
+    pub thumb_style: Style,
+    /// The style of the arrows at either end of the scrollbar.
+    pub arrow_style: Style,
+}
+
+impl Scrollbar {
+    /// Create a new scrollbar with the given orientation, and its default symbols.
+    pub const fn new(orientation: ScrollbarOrientation) -> Self {
+        Self {
+            orientation,
+            symbols: match orientation {
+                ScrollbarOrientation::Vertical => ScrollbarSymbols::VERTICAL,
+                ScrollbarOrientation::Horizontal => ScrollbarSymbols::HORIZONTAL,
+            },
+            track_style: Style::new(),
+            thumb_style: Style::new(),
+            arrow_style: Style::new(),
+        }
+    }
+
+    /// Create a new vertical scrollbar.
+    pub const fn vertical() -> Self {
+        Self::new(ScrollbarOrientation::Vertical)
+    }
+
+    /// Create a new horizontal scrollbar.
+    pub const fn horizontal() -> Self {
+        Self::new(ScrollbarOrientation::Horizontal)
+    }
+
+    /// Set the symbols for this scrollbar.
+    pub const fn with_symbols(mut self, symbols: ScrollbarSymbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Get the area directly next to the given one that this scrollbar would fit in (the column
+    /// to its right when vertical, or the row below it when horizontal).
+    pub const fn beside(&self, area: Area) -> Area {
+        match self.orientation {
+            ScrollbarOrientation::Vertical => Area::new(area.right(), area.y, 1, area.h),
+            ScrollbarOrientation::Horizontal => Area::new(area.x, area.bottom(), area.w, 1),
+        }
+    }
+
+    /// Render this scrollbar's arrows, track, and thumb along the given area.
+    ///
+    /// The area is clipped to the buffer, and the scrollbar runs along its first column (when
+    /// vertical) or row (when horizontal).
+    pub fn render(self, area: Area, buf: &mut impl RenderTarget, state: &ScrollbarState) {
+        let area = area.intersection(buf.area());
+        if area.is_empty() {
+            return;
+        }
+        let metrics = self.metrics(area, state);
+        let cell_at = |i: u16| match self.orientation {
+            ScrollbarOrientation::Vertical => (area.x, area.y + i),
+            ScrollbarOrientation::Horizontal => (area.x + i, area.y),
+        };
+
+        if let Some(begin) = self.symbols.begin {
+            let (x, y) = cell_at(0);
+            if let Some(cell) = buf.cell_mut(x, y) {
+                cell.set_char(begin).set_style(self.arrow_style);
+            }
+        }
+        if let Some(end) = self.symbols.end {
+            let (x, y) = cell_at(metrics.len - 1);
+            if let Some(cell) = buf.cell_mut(x, y) {
+                cell.set_char(end).set_style(self.arrow_style);
+            }
+        }
+
+        let thumb = metrics.thumb_start..metrics.thumb_start + metrics.thumb_len;
+        for i in 0..metrics.track_len {
+            let (x, y) = cell_at(metrics.track_start + i);
+            let Some(cell) = buf.cell_mut(x, y) else {
+                continue;
+            };
+            match (thumb.contains(&(i * 2)), thumb.contains(&(i * 2 + 1))) {
+                (true, true) => cell.set_char(self.symbols.thumb).set_style(self.thumb_style),
+                (true, false) => cell.set_char(self.symbols.thumb_halves.0)
+                    .set_style(self.thumb_style),
+                (false, true) => cell.set_char(self.symbols.thumb_halves.1)
+                    .set_style(self.thumb_style),
+                (false, false) => cell.set_char(self.symbols.track).set_style(self.track_style),
+            };
+        }
+    }
+
+    /// Update the given state with the user's mouse input, if it interacts with this scrollbar in
+    /// the given area.
+    ///
+    /// Clicking an arrow scrolls by one, clicking the track scrolls by a page towards the click,
+    /// and dragging the thumb scrolls along with it.
+    ///
+    /// Returns `true` if the state's offset changed.
+    pub fn handle_input(
+        &self,
+        area: Area,
+        state: &mut ScrollbarState,
+        input: &InputContext,
+    ) -> bool {
+        let was_down = state.mouse_down;
+        let is_down = input.is_mouse_button_down(MouseButton::Left);
+        state.mouse_down = is_down;
+        if !is_down {
+            state.drag = None;
+            return false;
+        }
+        let Some((mouse_x, mouse_y)) = input.mouse_pos() else {
+            return false;
+        };
+        let metrics = self.metrics(area, state);
+        let old_offset = state.offset;
+        // The mouse's position along the scrollbar, relative to the start of the track.
+        let pos = match self.orientation {
+            ScrollbarOrientation::Vertical => mouse_y as i32 - area.y as i32,
+            ScrollbarOrientation::Horizontal => mouse_x as i32 - area.x as i32,
+        } - metrics.track_start as i32;
+
+        if let Some(grab) = state.drag {
+            let free_units = metrics.track_len as i32 * 2 - metrics.thumb_len as i32;
+            if free_units > 0 {
+                let start = ((pos - grab) * 2).clamp(0, free_units);
+                state.offset = (start as usize * state.max_offset() + free_units as usize / 2)
+                    / free_units as usize;
+            }
+        } else if !was_down && area.contains(mouse_x, mouse_y) {
+            let unit = pos * 2;
+            let thumb_start = metrics.thumb_start as i32;
+            let thumb_end = thumb_start + metrics.thumb_len as i32;
+            if pos < 0 {
+                state.scroll_back(1);
+            } else if pos >= metrics.track_len as i32 {
+                state.scroll_forward(1);
+            } else if unit + 1 < thumb_start {
+                state.scroll_back(state.viewport_len.max(1));
+            } else if unit >= thumb_end {
+                state.scroll_forward(state.viewport_len.max(1));
+            } else {
+                state.drag = Some(pos - thumb_start / 2);
+            }
+        }
+
+        state.offset != old_offset
+    }
+
+    fn metrics(&self, area: Area, state: &ScrollbarState) -> ScrollbarMetrics {
+        let len = match self.orientation {
+            ScrollbarOrientation::Vertical => area.h,
+            ScrollbarOrientation::Horizontal => area.w,
+        };
+        let track_start = u16::from(self.symbols.begin.is_some()).min(len);
+        let track_len = len
+            .saturating_sub(track_start)
+            .saturating_sub(u16::from(self.symbols.end.is_some()));
+
+        // Lengths and positions along the track are measured in half cells.
+        let units = track_len as usize * 2;
+        let (thumb_start, thumb_len) = if units == 0 || state.content_len <= state.viewport_len {
+            (0, units)
+        } else {
+            let thumb_len = (units * state.viewport_len / state.content_len).clamp(1, units);
+            let max_offset = state.max_offset();
+            let thumb_start = ((units - thumb_len) * state.offset.min(max_offset) + max_offset / 2)
+                / max_offset;
+            (thumb_start, thumb_len)
+        };
+
+        ScrollbarMetrics {
+            len,
+            track_start,
+            track_len,
+            thumb_start: thumb_start as u16,
+            thumb_len: thumb_len as u16,
+        }
+    }
+}
+
+struct ScrollbarMetrics {
+    /// The scrollbar's length in cells, including its arrows.
+    len: u16,
+    /// The index of the track's first cell.
+    track_start: u16,
+    /// The track's length in cells.
+    track_len: u16,
+    /// The thumb's position along the track, in half cells.
+    thumb_start: u16,
+    /// The thumb's length, in half cells.
+    thumb_len: u16,
+}
+
+/// The direction a [`Scrollbar`] scrolls in.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ScrollbarOrientation {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+/// The symbols used to draw a [`Scrollbar`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ScrollbarSymbols {
+    /// The symbol for cells not covered by the thumb.
+    pub track: char,
+    /// The symbol for cells fully covered by the thumb.
+    pub thumb: char,
+    /// The symbols for cells where only the first or second half is covered by the thumb.
+    pub thumb_halves: (char, char),
+    /// The arrow drawn at the start of the scrollbar, if any.
+    pub begin: Option<char>,
+    /// The arrow drawn at the end of the scrollbar, if any.
+    pub end: Option<char>,
+}
+
+impl ScrollbarSymbols {
+    /// ```text
+    /// ▲
+    /// │
+    /// █
+    /// ▄
+    /// │
+    /// ▼
+    /// ```
+    pub const VERTICAL: Self = Self {
+        track: '│',
+        thumb: '█',
+        thumb_halves: ('▀', '▄'),
+        begin: Some('▲'),
+        end: Some('▼'),
+    };
+
+    /// ```text
+    /// ◄──██▌──►
+    /// ```
+    pub const HORIZONTAL: Self = Self {
+        track: '─',
+        thumb: '█',
+        thumb_halves: ('▌', '▐'),
+        begin: Some('◄'),
+        end: Some('►'),
+    };
+}
+
+
+
+/// The scrolling state displayed by a [`Scrollbar`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ScrollbarState {
+    /// The total length of the content being scrolled.
+    pub content_len: usize,
+    /// The length of the content that is visible at once.
+    pub viewport_len: usize,
+    /// The position of the start of the viewport within the content.
+    pub offset: usize,
+    /// Where the thumb was grabbed (in cells from its start) if it's being dragged.
+    drag: Option<i32>,
+    /// Whether the left mouse button was down the last time input was handled.
+    mouse_down: bool,
+}
+
+impl ScrollbarState {
+    /// Create a new state for content of the given length, seen through a viewport of the given
+    /// length.
+    pub const fn new(content_len: usize, viewport_len: usize) -> Self {
+        Self {
+            content_len,
+            viewport_len,
+            offset: 0,
+            drag: None,
+            mouse_down: false,
+        }
+    }
+
+    /// The largest offset that still fills the viewport with content.
+    pub const fn max_offset(&self) -> usize {
+        self.content_len.saturating_sub(self.viewport_len)
+    }
+
+    /// Whether the thumb is currently being dragged.
+    pub const fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Scroll towards the start of the content by the given amount.
+    pub fn scroll_back(&mut self, amount: usize) {
+        self.offset = self.offset.min(self.max_offset()).saturating_sub(amount);
+    }
+
+    /// Scroll towards the end of the content by the given amount.
+    pub fn scroll_forward(&mut self, amount: usize) {
+        self.offset = self.offset.saturating_add(amount).min(self.max_offset());
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Input;
+    use crate::test_utils::{buffer, column};
+
+    #[test]
+    fn scrollbar_thumb() {
+        let mut buf = buffer(1, 6);
+        let mut state = ScrollbarState::new(16, 4);
+        Scrollbar::vertical().render(buf.area, &mut buf, &state);
+        assert_eq!(column(&buf, 0), "▲█│││▼");
+
+        state.offset = 6;
+        Scrollbar::vertical().render(buf.area, &mut buf, &state);
+        assert_eq!(column(&buf, 0), "▲│▄▀│▼");
+
+        state.offset = 12;
+        Scrollbar::vertical().render(buf.area, &mut buf, &state);
+        assert_eq!(column(&buf, 0), "▲│││█▼");
+    }
+
+    #[test]
+    fn scrollbar_input() {
+        let area = Area::new(0, 0, 1, 6);
+        let scrollbar = Scrollbar::vertical();
+        let mut state = ScrollbarState::new(16, 4);
+        let mut input = InputContext::default();
+
+        // Clicking the track pages towards the click.
+        input.handle_input(Input::MouseMove(0, 3));
+        input.handle_input(Input::MouseDown(MouseButton::Left));
+        assert!(scrollbar.handle_input(area, &mut state, &input));
+        assert_eq!(state.offset, 4);
+        // Holding the button down doesn't keep paging.
+        assert!(!scrollbar.handle_input(area, &mut state, &input));
+        input.handle_input(Input::MouseUp(MouseButton::Left));
+        scrollbar.handle_input(area, &mut state, &input);
+
+        // Clicking the end arrow scrolls by one.
+        input.handle_input(Input::MouseMove(0, 5));
+        input.handle_input(Input::MouseDown(MouseButton::Left));
+        scrollbar.handle_input(area, &mut state, &input);
+        assert_eq!(state.offset, 5);
+        input.handle_input(Input::MouseUp(MouseButton::Left));
+        scrollbar.handle_input(area, &mut state, &input);
+
+        // Dragging the thumb to the end of the track.
+        state.offset = 0;
+        input.handle_input(Input::MouseMove(0, 1));
+        input.handle_input(Input::MouseDown(MouseButton::Left));
+        scrollbar.handle_input(area, &mut state, &input);
+        assert!(state.is_dragging());
+        input.handle_input(Input::MouseMove(0, 4));
+        scrollbar.handle_input(area, &mut state, &input);
+        assert_eq!(state.offset, 12);
+    }
+}
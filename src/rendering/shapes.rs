@@ -2,7 +2,7 @@
 
 
 
-use crate::{Area, Buffer, Style};
+use crate::{Area, RenderTarget, Style};
 
 
 
@@ -19,37 +19,34 @@ impl Rectangle {
         }
     }
 
-    pub fn render(self, area: Area, buf: &mut Buffer) {
+    /// Render this rectangle's outline around the edges of the given area.
+    ///
+    /// Any part of the outline outside of the buffer is clipped.
+    pub fn render(self, area: Area, buf: &mut impl RenderTarget) {
+        if area.is_empty() {
+            return;
+        }
         let (lt, rt, lb, rb, h, v) = self.rect_style.characters();
+        let right = area.right() - 1;
+        let bottom = area.bottom() - 1;
+        let mut set = |x: u16, y: u16, ch: char| {
+            if let Some(cell) = buf.cell_mut(x, y) {
+                cell.set_char(ch).set_style(self.style);
+            }
+        };
 
         for y in area.top()..area.bottom() {
-            buf.get_mut(area.x, y)
-                .set_char(v)
-                .set_style(self.style);
-            buf.get_mut(area.right().saturating_sub(1), y)
-                .set_char(v)
-                .set_style(self.style);
+            set(area.x, y, v);
+            set(right, y, v);
         }
         for x in area.left()..area.right() {
-            buf.get_mut(x, area.y)
-                .set_char(h)
-                .set_style(self.style);
-            buf.get_mut(x, area.bottom().saturating_sub(1))
-                .set_char(h)
-                .set_style(self.style);
+            set(x, area.y, h);
+            set(x, bottom, h);
         }
-        buf.get_mut(area.x, area.y)
-            .set_char(lt)
-            .set_style(self.style);
-        buf.get_mut(area.right().saturating_sub(1), area.y)
-            .set_char(rt)
-            .set_style(self.style);
-        buf.get_mut(area.right().saturating_sub(1), area.bottom().saturating_sub(1))
-            .set_char(rb)
-            .set_style(self.style);
-        buf.get_mut(area.x, area.bottom().saturating_sub(1))
-            .set_char(lb)
-            .set_style(self.style);
+        set(area.x, area.y, lt);
+        set(right, area.y, rt);
+        set(right, bottom, rb);
+        set(area.x, bottom, lb);
     }
 }
 
@@ -0,0 +1,242 @@
+//! Spinner Widget
+
+
+
+use std::time::{Duration, Instant};
+
+use crate::{Area, Frame, RenderTarget, Style};
+
+
+
+/// An animated symbol that shows that something is in progress, with an optional label.
+///
+/// Spinners advance based on how much time has passed since their [`SpinnerState`] was started,
+/// rather than on how many frames have been rendered, so they turn at the same speed no matter
+/// how often the program renders. Rendering a spinner requests a redraw for when its next frame
+/// is due, so it keeps turning even when there's no input.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// fn render_status(frame: &mut Frame, loading: &SpinnerState) {
+///     let area = Area::new(0, frame.rows.saturating_sub(1), frame.cols, 1);
+///     Spinner::new(SpinnerFrames::BRAILLE)
+///         .with_label("Loading")
+///         .with_style(Style::new().fg(Color::Cyan))
+///         .render(area, frame, loading);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Spinner<'a> {
+    /// The symbols the spinner cycles through, and how fast.
+    pub frames: SpinnerFrames<'a>,
+    /// The text drawn after the spinner, if any.
+    pub label: Option<&'a str>,
+    /// The style of the spinner's symbol.
+    pub style: Style,
+    /// The style of the label.
+    pub label_style: Style,
+}
+
+impl<'a> Spinner<'a> {
+    /// Create a new spinner with the given frames, without a label.
+    pub const fn new(frames: SpinnerFrames<'a>) -> Self {
+        Self {
+            frames,
+            label: None,
+            style: Style::new(),
+            label_style: Style::new(),
+        }
+    }
+
+    /// Draw the given text after the spinner.
+    pub const fn with_label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Set the style of the spinner's symbol.
+    pub const fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the style of the label.
+    pub const fn with_label_style(mut self, style: Style) -> Self {
+        self.label_style = style;
+        self
+    }
+
+    /// Render this spinner at the start of the given area, followed by its label, and request a
+    /// redraw for when its next frame is due.
+    pub fn render(self, area: Area, frame: &mut Frame, state: &SpinnerState) {
+        if let Some(next) = self.render_to(area, frame.buffer, state, frame.time) {
+            frame.request_redraw_at(next);
+        }
+    }
+
+    /// Render this spinner as it looks at the given time into any [`RenderTarget`], and return
+    /// when its next frame is due.
+    ///
+    /// Unlike [`Spinner::render`], this doesn't request a redraw, so it's up to the caller to
+    /// render it again by the returned time.
+    pub fn render_to(
+        self,
+        area: Area,
+        buf: &mut impl RenderTarget,
+        state: &SpinnerState,
+        now: Instant,
+    ) -> Option<Instant> {
+        let area = area.intersection(buf.area());
+        if area.is_empty() {
+            return None;
+        }
+        let elapsed = state.elapsed(now);
+        let symbol = self.frames.at(elapsed)?;
+        let (x, y) = buf.set_stringn(area.x, area.y, symbol, area.w as usize, self.style);
+        if let Some(label) = self.label {
+            let width = area.right().saturating_sub(x + 1) as usize;
+            buf.set_stringn(x + 1, y, label, width, self.label_style);
+        }
+        self.frames.next_change(elapsed).map(|next| state.started + next)
+    }
+}
+
+
+
+/// The symbols a [`Spinner`] cycles through, and how long each one is shown for.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SpinnerFrames<'a> {
+    /// The symbols, in order. Each one should be a single cell wide.
+    pub symbols: &'a [&'a str],
+    /// How long each symbol is shown for.
+    pub interval: Duration,
+}
+
+impl<'a> SpinnerFrames<'a> {
+    /// ```text
+    /// ⠋ ⠙ ⠹ ⠸ ⠼ ⠴ ⠦ ⠧ ⠇ ⠏
+    /// ```
+    pub const DOTS: Self = Self::new(
+        &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+        Duration::from_millis(80),
+    );
+
+    /// ```text
+    /// - \ | /
+    /// ```
+    pub const LINE: Self = Self::new(&["-", "\\", "|", "/"], Duration::from_millis(130));
+
+    /// ```text
+    /// ⣾ ⣽ ⣻ ⢿ ⡿ ⣟ ⣯ ⣷
+    /// ```
+    pub const BRAILLE: Self = Self::new(
+        &["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"],
+        Duration::from_millis(80),
+    );
+
+    /// ```text
+    /// ◜ ◠ ◝ ◞ ◡ ◟
+    /// ```
+    pub const ARC: Self = Self::new(
+        &["◜", "◠", "◝", "◞", "◡", "◟"],
+        Duration::from_millis(100),
+    );
+
+    /// ```text
+    /// ⠁ ⠂ ⠄ ⠂
+    /// ```
+    pub const BOUNCE: Self = Self::new(&["⠁", "⠂", "⠄", "⠂"], Duration::from_millis(120));
+
+    /// Create a custom set of frames.
+    pub const fn new(symbols: &'a [&'a str], interval: Duration) -> Self {
+        Self { symbols, interval }
+    }
+
+    /// The index of the symbol shown the given amount of time after the spinner started.
+    pub fn index(&self, elapsed: Duration) -> usize {
+        if self.symbols.is_empty() || self.interval.is_zero() {
+            return 0;
+        }
+        (elapsed.as_nanos() / self.interval.as_nanos() % self.symbols.len() as u128) as usize
+    }
+
+    /// The symbol shown the given amount of time after the spinner started, or `None` if there
+    /// aren't any symbols.
+    pub fn at(&self, elapsed: Duration) -> Option<&'a str> {
+        self.symbols.get(self.index(elapsed)).copied()
+    }
+
+    /// How long after the spinner started the next symbol is shown, or `None` if it never
+    /// changes.
+    fn next_change(&self, elapsed: Duration) -> Option<Duration> {
+        if self.symbols.len() < 2 || self.interval.is_zero() {
+            return None;
+        }
+        let steps = elapsed.as_nanos() / self.interval.as_nanos() + 1;
+        Some(Duration::from_nanos((steps * self.interval.as_nanos()) as u64))
+    }
+}
+
+
+
+/// When a [`Spinner`] started turning.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SpinnerState {
+    /// The time the spinner's first frame was shown.
+    pub started: Instant,
+}
+
+impl SpinnerState {
+    /// Create a new state for a spinner that starts turning at the given time.
+    pub const fn new(started: Instant) -> Self {
+        Self { started }
+    }
+
+    /// Start over from the first frame at the given time.
+    pub fn restart(&mut self, now: Instant) {
+        self.started = now;
+    }
+
+    /// How long the spinner has been turning for at the given time.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started)
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{buffer, text, with_frame};
+
+    fn render(spinner: Spinner, state: &SpinnerState, time: Instant) -> (String, Option<Instant>) {
+        let mut buffer = buffer(6, 1);
+        let next_redraw = with_frame(&mut buffer, |frame| {
+            frame.time = time;
+            spinner.render(Area::new(0, 0, 6, 1), frame, state);
+            frame.next_redraw
+        });
+        (text(&buffer), next_redraw)
+    }
+
+    #[test]
+    fn spinners_advance_on_time() {
+        let state = SpinnerState::new(Instant::now());
+        let ms = |n| state.started + Duration::from_millis(n);
+        let spinner = Spinner::new(SpinnerFrames::LINE).with_label("Loading");
+
+        assert_eq!(render(spinner, &state, ms(0)), ("- Load".to_string(), Some(ms(130))));
+        assert_eq!(render(spinner, &state, ms(129)), ("- Load".to_string(), Some(ms(130))));
+        assert_eq!(render(spinner, &state, ms(300)), ("| Load".to_string(), Some(ms(390))));
+        assert_eq!(render(spinner, &state, ms(530)), ("- Load".to_string(), Some(ms(650))));
+
+        let still = Spinner::new(SpinnerFrames::new(&["*"], Duration::from_millis(10)));
+        assert_eq!(render(still, &state, ms(50)), ("*     ".to_string(), None));
+        let empty = Spinner::new(SpinnerFrames::new(&[], Duration::from_millis(10)));
+        assert_eq!(render(empty, &state, ms(50)), ("      ".to_string(), None));
+    }
+}
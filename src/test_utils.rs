@@ -0,0 +1,43 @@
+//! Test Utilities
+
+
+
+use std::time::Instant;
+
+use crate::{Area, Buffer, Damage, Frame, Layers, Theme};
+
+
+
+/// Create a buffer of the given size at the origin, filled with empty cells.
+pub fn buffer(w: u16, h: u16) -> Buffer {
+    Buffer::new(Area::new(0, 0, w, h))
+}
+
+/// The symbols in the given row of a buffer.
+pub fn row(buf: &Buffer, y: u16) -> String {
+    (buf.area.left()..buf.area.right())
+        .map(|x| buf.content[buf.index_of(x, y)].symbol())
+        .collect()
+}
+
+/// The symbols in the given column of a buffer.
+pub fn column(buf: &Buffer, x: u16) -> String {
+    (buf.area.top()..buf.area.bottom())
+        .map(|y| buf.content[buf.index_of(x, y)].symbol())
+        .collect()
+}
+
+/// All of the symbols in a buffer, one row after another.
+pub fn text(buf: &Buffer) -> String {
+    buf.content.iter().map(|cell| cell.symbol()).collect()
+}
+
+/// Render a frame into the given buffer with `f`, starting now, with fully damaged contents, no
+/// layers, and the default theme.
+pub fn with_frame<R>(buf: &mut Buffer, f: impl FnOnce(&mut Frame) -> R) -> R {
+    let (mut damage, mut layers) = (Damage::full(), Layers::default());
+    let (mut theme, mut commands) = (Theme::default(), Vec::new());
+    let mut frame =
+        Frame::new(buf, &mut damage, &mut layers, &mut theme, &mut commands, Instant::now());
+    f(&mut frame)
+}
@@ -2,22 +2,30 @@
 
 
 
+mod animation;
 mod area;
 mod buffer;
 mod cell;
 mod color;
 mod command;
+mod damage;
 mod frame;
 mod input;
+mod layer;
 mod style;
+mod theme;
 mod modifier;
 
+pub use animation::*;
 pub use area::*;
 pub use buffer::*;
 pub use cell::*;
 pub use color::*;
 pub use command::*;
+pub use damage::*;
 pub use frame::*;
 pub use input::*;
+pub use layer::*;
 pub use style::*;
+pub use theme::*;
 pub use modifier::*;
@@ -0,0 +1,483 @@
+//! Animation & Tweening
+
+
+
+use std::time::{Duration, Instant};
+
+use super::{Area, Color, Frame};
+
+
+
+/// A curve that maps an animation's linear progress to eased progress.
+///
+/// Every easing function starts at `0.0` and ends at `1.0`. Some (like [`Easing::OutBack`])
+/// overshoot in between.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Easing {
+    /// Constant speed.
+    #[default]
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+    InSine,
+    OutSine,
+    InOutSine,
+    InExpo,
+    OutExpo,
+    InOutExpo,
+    /// Pull back slightly before moving forward.
+    InBack,
+    /// Overshoot the end slightly before settling.
+    OutBack,
+    /// Bounce against the end a few times before settling.
+    OutBounce,
+    /// Spring past the end a few times before settling.
+    OutElastic,
+}
+
+impl Easing {
+    /// Apply this easing function to the given progress, from `0.0` to `1.0`. Progress outside of
+    /// that range is clamped.
+    pub fn apply(self, t: f64) -> f64 {
+        use std::f64::consts::PI;
+
+        const BACK: f64 = 1.70158;
+
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::InQuad => t * t,
+            Self::OutQuad => 1.0 - (1.0 - t).powi(2),
+            Self::InOutQuad => if t < 0.5 {
+                2.0 * t * t
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+            },
+            Self::InCubic => t.powi(3),
+            Self::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Self::InOutCubic => if t < 0.5 {
+                4.0 * t.powi(3)
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+            },
+            Self::InSine => 1.0 - (t * PI / 2.0).cos(),
+            Self::OutSine => (t * PI / 2.0).sin(),
+            Self::InOutSine => -((t * PI).cos() - 1.0) / 2.0,
+            Self::InExpo => if t == 0.0 { 0.0 } else { 2f64.powf(10.0 * t - 10.0) },
+            Self::OutExpo => if t == 1.0 { 1.0 } else { 1.0 - 2f64.powf(-10.0 * t) },
+            Self::InOutExpo => if t == 0.0 || t == 1.0 {
+                t
+            } else if t < 0.5 {
+                2f64.powf(20.0 * t - 10.0) / 2.0
+            } else {
+                (2.0 - 2f64.powf(-20.0 * t + 10.0)) / 2.0
+            },
+            Self::InBack => (BACK + 1.0) * t.powi(3) - BACK * t * t,
+            Self::OutBack => 1.0 + (BACK + 1.0) * (t - 1.0).powi(3) + BACK * (t - 1.0).powi(2),
+            Self::OutBounce => {
+                const N: f64 = 7.5625;
+                const D: f64 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+            Self::OutElastic => if t == 0.0 || t == 1.0 {
+                t
+            } else {
+                2f64.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+            },
+        }
+    }
+}
+
+
+
+/// A value that can be smoothly transitioned from one state to another.
+pub trait Tweenable: Clone {
+    /// Get the value that is `t` of the way (usually from `0.0` to `1.0`) from this one to
+    /// another one. Eased progress can go slightly past either end.
+    fn tween(&self, to: &Self, t: f64) -> Self;
+}
+
+impl Tweenable for f64 {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Tweenable for f32 {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        (*self as f64).tween(&(*to as f64), t) as f32
+    }
+}
+
+impl Tweenable for u16 {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        (*self as f64).tween(&(*to as f64), t).round().clamp(0.0, u16::MAX as f64) as u16
+    }
+}
+
+impl Tweenable for (u16, u16) {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        (self.0.tween(&to.0, t), self.1.tween(&to.1, t))
+    }
+}
+
+impl Tweenable for Area {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        Area::new(
+            self.x.tween(&to.x, t),
+            self.y.tween(&to.y, t),
+            self.w.tween(&to.w, t),
+            self.h.tween(&to.h, t),
+        )
+    }
+}
+
+/// Colors are blended in RGB with [`Color::blend`].
+impl Tweenable for Color {
+    fn tween(&self, to: &Self, t: f64) -> Self {
+        self.blend(*to, t)
+    }
+}
+
+
+
+/// Something that produces a value over time.
+pub trait Animation {
+    /// The type of value this animation produces.
+    type Value;
+
+    /// Get the value the given amount of time after the animation started.
+    fn sample(&self, elapsed: Duration) -> Self::Value;
+
+    /// How long this animation runs for, or `None` if it never ends.
+    fn duration(&self) -> Option<Duration>;
+}
+
+/// A transition between two values.
+///
+/// ## Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use dreg::*;
+///
+/// let slide = Tween::new(0u16, 40, Duration::from_millis(200)).with_easing(Easing::OutCubic);
+/// assert_eq!(slide.sample(Duration::ZERO), 0);
+/// assert_eq!(slide.sample(Duration::from_millis(100)), 35);
+/// assert_eq!(slide.sample(Duration::from_secs(1)), 40);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tween<T> {
+    /// The value at the start of the tween.
+    pub from: T,
+    /// The value at the end of the tween.
+    pub to: T,
+    /// How long the transition takes, not counting the delay.
+    pub duration: Duration,
+    /// How long to wait (at the starting value) before the transition begins.
+    pub delay: Duration,
+    /// How the value moves between the start and end.
+    pub easing: Easing,
+}
+
+impl<T: Tweenable> Tween<T> {
+    /// Create a new linear tween between two values.
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            delay: Duration::ZERO,
+            easing: Easing::Linear,
+        }
+    }
+
+    /// Set how the value moves between the start and end.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Wait for the given amount of time before the transition begins.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// How far along the transition is the given amount of time after the tween started, from
+    /// `0.0` to `1.0`, before easing.
+    pub fn progress(&self, elapsed: Duration) -> f64 {
+        let elapsed = elapsed.saturating_sub(self.delay);
+        if elapsed >= self.duration {
+            1.0
+        } else {
+            elapsed.as_secs_f64() / self.duration.as_secs_f64()
+        }
+    }
+}
+
+impl<T: Tweenable> Animation for Tween<T> {
+    type Value = T;
+
+    fn sample(&self, elapsed: Duration) -> T {
+        match self.progress(elapsed) {
+            0.0 => self.from.clone(),
+            1.0 => self.to.clone(),
+            t => self.from.tween(&self.to, self.easing.apply(t)),
+        }
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        Some(self.delay + self.duration)
+    }
+}
+
+/// A sequence of transitions, each starting where the last one ended.
+///
+/// ## Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use dreg::*;
+///
+/// // Fade a notification in, keep it up for a few seconds, and then fade it out.
+/// let second = Duration::from_secs(1);
+/// let fade = Timeline::new(Color::Rgb(0, 0, 0))
+///     .then(Color::Rgb(200, 200, 200), second / 4, Easing::OutQuad)
+///     .hold(second * 3)
+///     .then(Color::Rgb(0, 0, 0), second / 2, Easing::InQuad);
+/// assert_eq!(fade.sample(second * 2), Color::Rgb(200, 200, 200));
+/// assert_eq!(fade.duration(), Some(second * 15 / 4));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeline<T> {
+    start: T,
+    steps: Vec<Tween<T>>,
+    looping: bool,
+}
+
+impl<T: Tweenable> Timeline<T> {
+    /// Create a new timeline that starts (and, until any steps are added, stays) at the given
+    /// value.
+    pub fn new(start: T) -> Self {
+        Self {
+            start,
+            steps: Vec::new(),
+            looping: false,
+        }
+    }
+
+    /// Transition to the given value once the previous steps are done.
+    pub fn then(mut self, to: T, duration: Duration, easing: Easing) -> Self {
+        let from = self.end().clone();
+        self.steps.push(Tween::new(from, to, duration).with_easing(easing));
+        self
+    }
+
+    /// Stay at the current value for the given amount of time.
+    pub fn hold(self, duration: Duration) -> Self {
+        let value = self.end().clone();
+        self.then(value, duration, Easing::Linear)
+    }
+
+    /// Start over from the beginning whenever the timeline ends, forever.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// The value this timeline ends at.
+    pub fn end(&self) -> &T {
+        self.steps.last().map_or(&self.start, |step| &step.to)
+    }
+
+    fn total(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+}
+
+impl<T: Tweenable> Animation for Timeline<T> {
+    type Value = T;
+
+    fn sample(&self, elapsed: Duration) -> T {
+        let total = self.total();
+        let mut remaining = if self.looping && !total.is_zero() {
+            Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64)
+        } else {
+            elapsed
+        };
+        for step in &self.steps {
+            if remaining < step.duration {
+                return step.sample(remaining);
+            }
+            remaining -= step.duration;
+        }
+        self.end().clone()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        if self.looping && !self.total().is_zero() {
+            None
+        } else {
+            Some(self.total())
+        }
+    }
+}
+
+
+
+/// Keeps track of when an [`Animation`] was started, and keeps frames coming while it runs.
+///
+/// Reading the animation's value with [`Animator::value`] requests another frame whenever the
+/// animation is still running, so programs that [render on demand](crate::RenderMode::OnDemand)
+/// animate smoothly without having to schedule redraws themselves, and go back to waiting for
+/// input once every animation has finished.
+///
+/// ## Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use dreg::*;
+///
+/// struct Sidebar {
+///     slide: Animator<Tween<u16>>,
+/// }
+///
+/// impl Sidebar {
+///     fn open(&mut self, now: std::time::Instant) {
+///         self.slide.start(now);
+///     }
+///
+///     fn render(&self, frame: &mut Frame) {
+///         let width = self.slide.value(frame);
+///         let area = Area::new(0, 0, width, frame.rows);
+///         Rectangle::new(Style::default()).render(area, frame.buffer);
+///     }
+/// }
+///
+/// let sidebar = Sidebar {
+///     slide: Animator::new(Tween::new(0, 30, Duration::from_millis(250))),
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animator<A> {
+    /// The animation being played.
+    pub animation: A,
+    started: Option<Instant>,
+}
+
+impl<A: Animation> Animator<A> {
+    /// Create a new animator for the given animation, which won't run until it's started.
+    pub fn new(animation: A) -> Self {
+        Self { animation, started: None }
+    }
+
+    /// Start (or restart) the animation at the given time. Use [`Frame::time`] when starting it
+    /// while rendering.
+    pub fn start(&mut self, now: Instant) {
+        self.started = Some(now);
+    }
+
+    /// Stop the animation, resetting it to its starting value.
+    pub fn stop(&mut self) {
+        self.started = None;
+    }
+
+    /// How long the animation has been running for at the given time, or `None` if it hasn't
+    /// been started.
+    pub fn elapsed(&self, now: Instant) -> Option<Duration> {
+        self.started.map(|started| now.saturating_duration_since(started))
+    }
+
+    /// Whether the animation has been started, and hasn't finished yet at the given time.
+    pub fn is_running(&self, now: Instant) -> bool {
+        self.elapsed(now).is_some_and(|elapsed| {
+            self.animation.duration().is_none_or(|duration| elapsed < duration)
+        })
+    }
+
+    /// Get the animation's value at the given time.
+    pub fn value_at(&self, now: Instant) -> A::Value {
+        self.animation.sample(self.elapsed(now).unwrap_or_default())
+    }
+
+    /// Get the animation's value for this frame, requesting another frame if it's still running.
+    pub fn value(&self, frame: &mut Frame) -> A::Value {
+        if self.is_running(frame.time) {
+            frame.request_animation_frame();
+        }
+        self.value_at(frame.time)
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn easing_endpoints() {
+        let easings = [
+            Easing::Linear, Easing::InQuad, Easing::OutQuad, Easing::InOutQuad, Easing::InCubic,
+            Easing::OutCubic, Easing::InOutCubic, Easing::InSine, Easing::OutSine,
+            Easing::InOutSine, Easing::InExpo, Easing::OutExpo, Easing::InOutExpo, Easing::InBack,
+            Easing::OutBack, Easing::OutBounce, Easing::OutElastic,
+        ];
+        for easing in easings {
+            assert!(easing.apply(0.0).abs() < 1e-9, "{easing:?}");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-9, "{easing:?}");
+        }
+        assert_eq!(Easing::InOutQuad.apply(0.5), 0.5);
+        assert!(Easing::OutBack.apply(0.7) > 1.0);
+    }
+
+    #[test]
+    fn tweens_and_timelines() {
+        let tween = Tween::new(Area::new(0, 0, 10, 4), Area::new(10, 2, 20, 4), MS * 100)
+            .with_delay(MS * 50);
+        assert_eq!(tween.sample(MS * 40), Area::new(0, 0, 10, 4));
+        assert_eq!(tween.sample(MS * 100), Area::new(5, 1, 15, 4));
+        assert_eq!(tween.duration(), Some(MS * 150));
+
+        let timeline = Timeline::new(0u16)
+            .then(10, MS * 10, Easing::Linear)
+            .hold(MS * 10)
+            .looping();
+        assert_eq!(timeline.sample(MS * 5), 5);
+        assert_eq!(timeline.sample(MS * 15), 10);
+        assert_eq!(timeline.sample(MS * 25), 5);
+        assert_eq!(timeline.duration(), None);
+    }
+
+    #[test]
+    fn animators() {
+        let start = Instant::now();
+        let mut animator = Animator::new(Tween::new(0.0, 1.0, MS * 100));
+        assert!(!animator.is_running(start));
+        assert_eq!(animator.value_at(start + MS * 50), 0.0);
+
+        animator.start(start);
+        assert!(animator.is_running(start + MS * 50));
+        assert_eq!(animator.value_at(start + MS * 50), 0.5);
+        assert!(!animator.is_running(start + MS * 100));
+        assert_eq!(animator.value_at(start + MS * 500), 1.0);
+    }
+}
@@ -53,6 +53,17 @@ impl Area {
         self.y.saturating_add(self.h)
     }
 
+    /// Get the area covered by both this area and the other one.
+    ///
+    /// If the two areas don't overlap, the result is empty.
+    pub fn intersection(self, other: Self) -> Self {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Self::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+
     /// Returns true if the given coordinates are within this area.
     pub fn contains(&self, x: u16, y: u16) -> bool {
         x >= self.x
@@ -141,7 +152,6 @@ impl Area {
     /// Get a set of 1-height areas that will fit into this one's rows.
     pub fn rows(&self) -> Vec<Self> {
         (0..self.h)
-            .into_iter()
             .map(|row_index| {
                 Self::new(self.x, self.y + row_index, self.w, 1)
             })
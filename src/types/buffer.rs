@@ -2,6 +2,8 @@
 
 
 
+use std::ops::Range;
+
 use unicode_width::UnicodeWidthStr as _;
 
 use super::{Area, Cell, Style};
@@ -9,7 +11,7 @@ use super::{Area, Cell, Style};
 
 
 /// A set of [`Cell`]s.
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct Buffer {
     /// The buffer's area.
     pub area: Area,
@@ -26,6 +28,18 @@ impl Buffer {
         }
     }
 
+    /// Create a new buffer covering the given [`Area`], filled with empty [`Cell`]s.
+    ///
+    /// The area doesn't need to start at the origin, or fit on the screen. This is useful for
+    /// rendering content off-screen, and then copying part of it elsewhere with
+    /// [`Buffer::blit`].
+    pub fn new(area: Area) -> Self {
+        Self {
+            area,
+            content: vec![Cell::EMPTY; area.w as usize * area.h as usize],
+        }
+    }
+
     /// Reset all [`Cell`]s in this buffer.
     pub fn reset(&mut self) {
         for cell in &mut self.content {
@@ -36,7 +50,7 @@ impl Buffer {
     /// Resize this buffer so that the mapped area matches the given [`Area`] and that the buffer
     /// length is equal to `area.w` * `area.h`.
     pub fn resize(&mut self, area: Area) {
-        let length = (area.w * area.h) as usize;
+        let length = area.w as usize * area.h as usize;
         if self.content.len() > length {
             self.content.truncate(length);
         } else {
@@ -70,6 +84,9 @@ impl Buffer {
         T: AsRef<str>,
         S: Into<Style>,
     {
+        if y < self.area.top() || y >= self.area.bottom() {
+            return (x, y);
+        }
         let max_width = max_width.try_into().unwrap_or(u16::MAX);
         let mut remaining_width = self.area.right().saturating_sub(x).min(max_width);
         let graphemes = unicode_segmentation::UnicodeSegmentation::graphemes(string.as_ref(), true)
@@ -81,42 +98,242 @@ impl Buffer {
             });
         let style = style.into();
         for (symbol, width) in graphemes {
-            self.get_mut(x, y).set_symbol(symbol).set_style(style);
+            // Graphemes left of the buffer's area are clipped.
+            if x >= self.area.left() {
+                self.get_mut(x, y).set_symbol(symbol).set_style(style);
+            }
             let next_symbol = x + width;
             x += 1;
             // Reset following cells if multi-width (they would be hidden by the grapheme),
             while x < next_symbol {
-                self.get_mut(x, y).reset();
+                if x >= self.area.left() {
+                    self.get_mut(x, y).reset();
+                }
                 x += 1;
             }
         }
         (x, y)
     }
 
+    /// Write a string to this buffer as a hyperlink to the given target, starting at the position
+    /// (x, y).
+    ///
+    /// See [`Cell::set_hyperlink`] for details.
+    pub fn set_hyperlink<T, S>(
+        &mut self,
+        x: u16,
+        y: u16,
+        string: T,
+        target: &str,
+        style: S,
+    ) -> (u16, u16)
+    where
+        T: AsRef<str>,
+        S: Into<Style>,
+    {
+        let (end_x, _) = self.set_stringn(x, y, string, usize::MAX, style);
+        for x in x.max(self.area.left())..end_x.min(self.area.right()) {
+            self.get_mut(x, y).set_hyperlink(Some(target));
+        }
+        (end_x, y)
+    }
+
+    /// Apply a style to every [`Cell`] in the given area, leaving their symbols alone.
+    ///
+    /// This is useful for highlighting content that has already been rendered, like a selection
+    /// or search results. Parts of the area outside of this buffer are ignored.
+    pub fn set_style<S: Into<Style>>(&mut self, area: Area, style: S) {
+        let style = style.into();
+        let area = area.intersection(self.area);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                self.get_mut(x, y).set_style(style);
+            }
+        }
+    }
+
     /// Get a mutable reference to the [`Cell`] at the given position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside of this buffer's area. Use [`Buffer::try_get_mut`] when
+    /// the position may be out of bounds.
     pub fn get_mut(&mut self, x: u16, y: u16) -> &mut Cell {
-        let i = self.index_of(x, y);
+        let Some(i) = self.try_index_of(x, y) else {
+            panic!("position ({x}, {y}) is outside of the buffer's area {:?}", self.area);
+        };
         &mut self.content[i]
     }
 
+    /// Get a reference to the [`Cell`] at the given position, or `None` if the position is outside
+    /// of this buffer's area.
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.try_index_of(x, y).map(|i| &self.content[i])
+    }
+
+    /// Get a mutable reference to the [`Cell`] at the given position, or `None` if the position
+    /// is outside of this buffer's area.
+    pub fn try_get_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell> {
+        self.try_index_of(x, y).map(|i| &mut self.content[i])
+    }
+
+    /// Get a reference to the [`Cell`] at the given position, or to [`Cell::EMPTY`] if the
+    /// position is outside of this buffer's area.
+    pub fn cell_at(&self, x: u16, y: u16) -> &Cell {
+        static EMPTY: Cell = Cell::EMPTY;
+        self.get(x, y).unwrap_or(&EMPTY)
+    }
+
+    /// Get the index of the [`Cell`] at the given position, or `None` if the position is outside
+    /// of this buffer's area.
+    pub fn try_index_of(&self, x: u16, y: u16) -> Option<usize> {
+        self.area.contains(x, y).then(|| self.index_of(x, y))
+    }
+
     /// Get the index of the [`Cell`] at the given position.
+    ///
+    /// The position must be within this buffer's area. Debug builds panic if it isn't, while
+    /// release builds return a meaningless index: positions above or left of the area wrap
+    /// around, and positions past its right edge land on a later row. Use
+    /// [`Buffer::try_index_of`] when the position may be out of bounds.
     pub fn index_of(&self, x: u16, y: u16) -> usize {
-        ((y - self.area.y) * self.area.w + (x - self.area.x)) as usize
+        debug_assert!(
+            self.area.contains(x, y),
+            "position ({x}, {y}) is outside of the buffer's area {:?}",
+            self.area,
+        );
+        y.wrapping_sub(self.area.y) as usize * self.area.w as usize
+            + x.wrapping_sub(self.area.x) as usize
     }
 
     /// Get the position of the [`Cell`] at the given index.
     pub fn pos_of(&self, i: usize) -> (u16, u16) {
         (
-            self.area.x + (i as u16) % self.area.w,
-            self.area.y + (i as u16) / self.area.w,
+            self.area.x + (i % self.area.w as usize) as u16,
+            self.area.y + (i / self.area.w as usize) as u16,
         )
     }
 
+    /// Copy the cells in `src_area` of the `src` buffer into this buffer, with the top-left
+    /// corner of the copied region at (x, y).
+    ///
+    /// Anything outside of either buffer is clipped. Multi-width graphemes that would be cut off
+    /// by the right edge of the copied region are replaced with empty cells, so that they don't
+    /// spill over into the cells next to it.
+    pub fn blit(&mut self, x: u16, y: u16, src: &Buffer, src_area: Area) {
+        self.copy_region(x, y, src, src_area, |target, cell| target.clone_from(cell));
+    }
+
+    /// Like [`Buffer::blit`], but cells for which `is_transparent` returns `true` are skipped,
+    /// leaving the cells below them untouched.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use dreg::*;
+    ///
+    /// let mut overlay = Buffer::new(Area::new(0, 0, 10, 1));
+    /// overlay.set_string(0, 0, "hi", Style::default().bg(Color::Blue));
+    ///
+    /// // Only copy the cells that were given a background.
+    /// let mut buf = Buffer::new(Area::new(0, 0, 10, 1));
+    /// buf.merge(0, 0, &overlay, overlay.area, |cell| cell.bg == Color::Reset);
+    /// ```
+    pub fn merge<F>(&mut self, x: u16, y: u16, src: &Buffer, src_area: Area, is_transparent: F)
+    where
+        F: Fn(&Cell) -> bool,
+    {
+        self.copy_region(x, y, src, src_area, |target, cell| {
+            if !is_transparent(cell) {
+                target.clone_from(cell);
+            }
+        });
+    }
+
+    fn copy_region<F>(&mut self, x: u16, y: u16, src: &Buffer, src_area: Area, mut copy: F)
+    where
+        F: FnMut(&mut Cell, &Cell),
+    {
+        let src_area = src_area.intersection(src.area);
+        // The region being written to, clipped to this buffer.
+        let dst_area = Area::new(x, y, src_area.w, src_area.h).intersection(self.area);
+        if dst_area.is_empty() {
+            return;
+        }
+        let src_x = src_area.x + (dst_area.x - x);
+        let src_y = src_area.y + (dst_area.y - y);
+
+        for row in 0..dst_area.h {
+            let src_start = src.index_of(src_x, src_y + row);
+            let dst_start = self.index_of(dst_area.x, dst_area.y + row);
+            let cells = &src.content[src_start..src_start + dst_area.w as usize];
+            for (col, cell) in cells.iter().enumerate() {
+                let target = &mut self.content[dst_start + col];
+                if cell.symbol().width() > dst_area.w as usize - col {
+                    let mut clipped = Cell::EMPTY;
+                    clipped.bg = cell.bg;
+                    copy(target, &clipped);
+                } else {
+                    copy(target, cell);
+                }
+            }
+        }
+    }
+
+    /// Get a view of the given area of this buffer.
+    ///
+    /// The view uses its own coordinates, starting at (0, 0), and clips all of its writes to the
+    /// area (which is itself clipped to this buffer's area).
+    pub fn view_mut(&mut self, area: Area) -> BufferView<'_> {
+        BufferView {
+            area: area.intersection(self.area),
+            buffer: self,
+        }
+    }
+
+    /// Get the position and new state of every [`Cell`] that changed between this buffer and
+    /// `other`.
+    ///
+    /// See [`Buffer::diff_into`] for a version of this that reuses its allocation.
     pub fn diff<'a>(&self, other: &'a Self) -> Vec<(u16, u16, &'a Cell)> {
-        let prev_buf = &self.content;
-        let next_buf = &other.content;
+        let mut updates = Vec::new();
+        self.diff_into(other, &mut updates);
+        updates
+            .into_iter()
+            .map(|i| {
+                let (x, y) = self.pos_of(i);
+                (x, y, &other.content[i])
+            })
+            .collect()
+    }
+
+    /// Replace the contents of `updates` with the indices of every [`Cell`] that changed between
+    /// this buffer and `other`, in order.
+    pub fn diff_into(&self, other: &Self, updates: &mut Vec<usize>) {
+        updates.clear();
+        self.diff_range(other, 0..self.content.len().min(other.content.len()), updates);
+    }
+
+    /// Replace the contents of `updates` with the indices of every [`Cell`] that changed between
+    /// this buffer and `other` within the given ranges of rows, in order.
+    ///
+    /// The ranges must be sorted and not overlap, like those returned by
+    /// [`Damage::rows`](crate::Damage::rows). Rows outside of them are assumed to be unchanged.
+    pub fn diff_rows_into(&self, other: &Self, rows: &[Range<u16>], updates: &mut Vec<usize>) {
+        updates.clear();
+        let len = self.content.len().min(other.content.len());
+        let w = self.area.w as usize;
+        for range in rows {
+            let start = range.start.saturating_sub(self.area.y) as usize * w;
+            let end = range.end.saturating_sub(self.area.y) as usize * w;
+            self.diff_range(other, start.min(len)..end.min(len), updates);
+        }
+    }
+
+    fn diff_range(&self, other: &Self, range: Range<usize>, updates: &mut Vec<usize>) {
+        let prev_buf = &self.content[range.clone()];
+        let next_buf = &other.content[range.clone()];
 
-        let mut updates: Vec<(u16, u16, &Cell)> = vec![];
         // Cells invalidated by drawing/replacing preceding multi-width characters:
         let mut invalidated: usize = 0;
         // Cells from the current buffer to skip due to preceding multi-width characters taking
@@ -124,18 +341,384 @@ impl Buffer {
         let mut to_skip: usize = 0;
         for (i, (current, previous)) in next_buf.iter().zip(prev_buf.iter()).enumerate() {
             if (current != previous || invalidated > 0) && to_skip == 0 {
-                let (x, y) = self.pos_of(i);
-                updates.push((x, y, &next_buf[i]));
+                updates.push(range.start + i);
             }
 
-            to_skip = current.symbol().width().saturating_sub(1);
+            let current_width = current.symbol().width();
+            to_skip = current_width.saturating_sub(1);
 
-            let affected_width = std::cmp::max(
-                current.symbol().width(),
-                previous.symbol().width(),
-            );
+            let affected_width = std::cmp::max(current_width, previous.symbol().width());
             invalidated = std::cmp::max(affected_width, invalidated).saturating_sub(1);
         }
-        updates
+    }
+}
+
+
+
+/// A mutable view of part of a [`Buffer`], created with [`Buffer::view_mut`].
+///
+/// All positions given to a view are relative to the top-left corner of its area, and writes
+/// outside of the area are ignored.
+pub struct BufferView<'a> {
+    buffer: &'a mut Buffer,
+    area: Area,
+}
+
+impl BufferView<'_> {
+    /// The area this view covers, in its own coordinates (so `x` and `y` are always 0).
+    pub fn area(&self) -> Area {
+        Area::new(0, 0, self.area.w, self.area.h)
+    }
+
+    /// The area this view covers, in the coordinates of the underlying [`Buffer`].
+    pub fn buffer_area(&self) -> Area {
+        self.area
+    }
+
+    /// Get a mutable reference to the [`Cell`] at the given position, if it's within this view.
+    pub fn get_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell> {
+        if x >= self.area.w || y >= self.area.h {
+            return None;
+        }
+        Some(self.buffer.get_mut(self.area.x + x, self.area.y + y))
+    }
+
+    /// Write a string to this view, starting at the position (x, y).
+    pub fn set_string<T, S>(&mut self, x: u16, y: u16, string: T, style: S)
+    where
+        T: AsRef<str>,
+        S: Into<Style>,
+    {
+        self.set_stringn(x, y, string, usize::MAX, style);
+    }
+
+    /// Write at most the first `n` characters of a string to this view, stopping at the right
+    /// edge of the view.
+    pub fn set_stringn<T, S>(
+        &mut self,
+        x: u16,
+        y: u16,
+        string: T,
+        max_width: usize,
+        style: S,
+    ) -> (u16, u16)
+    where
+        T: AsRef<str>,
+        S: Into<Style>,
+    {
+        if x >= self.area.w || y >= self.area.h {
+            return (x, y);
+        }
+        let max_width = max_width.min((self.area.w - x) as usize);
+        let (end_x, _) = self.buffer.set_stringn(
+            self.area.x + x,
+            self.area.y + y,
+            string,
+            max_width,
+            style,
+        );
+        (end_x - self.area.x, y)
+    }
+
+    /// Write a string to this view as a hyperlink to the given target, starting at the position
+    /// (x, y).
+    pub fn set_hyperlink<T, S>(
+        &mut self,
+        x: u16,
+        y: u16,
+        string: T,
+        target: &str,
+        style: S,
+    ) -> (u16, u16)
+    where
+        T: AsRef<str>,
+        S: Into<Style>,
+    {
+        let (end_x, _) = self.set_stringn(x, y, string, usize::MAX, style);
+        for x in x..end_x {
+            if let Some(cell) = self.get_mut(x, y) {
+                cell.set_hyperlink(Some(target));
+            }
+        }
+        (end_x, y)
+    }
+
+    /// Apply a style to every [`Cell`] in the given area (in this view's coordinates).
+    pub fn set_style<S: Into<Style>>(&mut self, area: Area, style: S) {
+        let area = area.intersection(self.area());
+        let area = Area::new(self.area.x + area.x, self.area.y + area.y, area.w, area.h);
+        self.buffer.set_style(area, style);
+    }
+
+    /// Copy the cells in `src_area` of the `src` buffer into this view, with the top-left corner
+    /// of the copied region at (x, y), clipping it to this view the same way as [`Buffer::blit`].
+    pub fn blit(&mut self, x: u16, y: u16, src: &Buffer, src_area: Area) {
+        let src_area = src_area.intersection(src.area);
+        let dst_area = Area::new(x, y, src_area.w, src_area.h).intersection(self.area());
+        if dst_area.is_empty() {
+            return;
+        }
+        let src_area = Area::new(
+            src_area.x + (dst_area.x - x),
+            src_area.y + (dst_area.y - y),
+            dst_area.w,
+            dst_area.h,
+        );
+        self.buffer.blit(self.area.x + dst_area.x, self.area.y + dst_area.y, src, src_area);
+    }
+
+    /// Reset all [`Cell`]s in this view.
+    pub fn reset(&mut self) {
+        for y in self.area.top()..self.area.bottom() {
+            for x in self.area.left()..self.area.right() {
+                self.buffer.get_mut(x, y).reset();
+            }
+        }
+    }
+
+    /// Get a view of the given area (in this view's coordinates) of this view.
+    pub fn view_mut(&mut self, area: Area) -> BufferView<'_> {
+        let area = area.intersection(self.area());
+        BufferView {
+            area: Area::new(self.area.x + area.x, self.area.y + area.y, area.w, area.h),
+            buffer: self.buffer,
+        }
+    }
+}
+
+
+
+/// Something that widgets can be rendered into, either a whole [`Buffer`] or a [`BufferView`]
+/// of part of one.
+pub trait RenderTarget {
+    /// The area that can be drawn to, in this target's own coordinates.
+    fn area(&self) -> Area;
+
+    /// Get a mutable reference to the [`Cell`] at the given position, if it's within this target.
+    fn cell_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell>;
+
+    /// Write at most the first `n` characters of a string to this target, starting at the
+    /// position (x, y).
+    fn set_stringn<T, S>(
+        &mut self,
+        x: u16,
+        y: u16,
+        string: T,
+        max_width: usize,
+        style: S,
+    ) -> (u16, u16)
+    where
+        T: AsRef<str>,
+        S: Into<Style>;
+
+    /// Copy the cells in `src_area` of the `src` buffer into this target, with the top-left
+    /// corner of the copied region at (x, y), clipping it the same way as [`Buffer::blit`].
+    fn blit(&mut self, x: u16, y: u16, src: &Buffer, src_area: Area);
+}
+
+impl RenderTarget for Buffer {
+    fn area(&self) -> Area {
+        self.area
+    }
+
+    fn cell_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell> {
+        self.try_get_mut(x, y)
+    }
+
+    fn set_stringn<T, S>(
+        &mut self,
+        x: u16,
+        y: u16,
+        string: T,
+        max_width: usize,
+        style: S,
+    ) -> (u16, u16)
+    where
+        T: AsRef<str>,
+        S: Into<Style>,
+    {
+        Buffer::set_stringn(self, x, y, string, max_width, style)
+    }
+
+    fn blit(&mut self, x: u16, y: u16, src: &Buffer, src_area: Area) {
+        Buffer::blit(self, x, y, src, src_area);
+    }
+}
+
+impl RenderTarget for BufferView<'_> {
+    fn area(&self) -> Area {
+        BufferView::area(self)
+    }
+
+    fn cell_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell> {
+        self.get_mut(x, y)
+    }
+
+    fn set_stringn<T, S>(
+        &mut self,
+        x: u16,
+        y: u16,
+        string: T,
+        max_width: usize,
+        style: S,
+    ) -> (u16, u16)
+    where
+        T: AsRef<str>,
+        S: Into<Style>,
+    {
+        BufferView::set_stringn(self, x, y, string, max_width, style)
+    }
+
+    fn blit(&mut self, x: u16, y: u16, src: &Buffer, src_area: Area) {
+        BufferView::blit(self, x, y, src, src_area);
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, Rectangle, ScrollView};
+    use crate::test_utils::row;
+
+    #[test]
+    fn out_of_bounds_access() {
+        let mut buf = Buffer::new(Area::new(2, 2, 3, 1));
+        assert!(buf.get(1, 2).is_none());
+        assert!(buf.get(5, 2).is_none());
+        assert!(buf.try_get_mut(2, 3).is_none());
+        assert_eq!(buf.cell_at(0, 0), &Cell::EMPTY);
+
+        buf.set_string(0, 2, "abcdefg", Style::default());
+        assert_eq!(row(&buf, 2), "cde");
+        buf.set_string(0, 0, "hidden", Style::default());
+        buf.set_string(0, 9, "hidden", Style::default());
+        assert_eq!(row(&buf, 2), "cde");
+
+        crate::Rectangle::new(Style::default()).render(Area::new(4, 2, 0, 0), &mut buf);
+        crate::Rectangle::new(Style::default()).render(Area::new(0, 0, 3, 3), &mut buf);
+        assert_eq!(row(&buf, 2), "┘de");
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the buffer's area")]
+    fn get_mut_past_the_right_edge() {
+        // This would land on the first cell of the next row if it weren't checked.
+        Buffer::new(Area::new(0, 0, 3, 2)).get_mut(3, 0);
+    }
+
+    #[test]
+    fn buffer_merging() {
+        let mut src = Buffer::new(Area::new(0, 0, 4, 1));
+        src.set_string(0, 0, "ab", Style::default().bg(Color::Blue));
+        src.set_string(2, 0, "cd", Style::default());
+
+        let mut buf = Buffer::new(Area::new(0, 0, 6, 1));
+        buf.set_string(0, 0, "012345", Style::default());
+        buf.merge(1, 0, &src, src.area, |cell| cell.bg == Color::Reset);
+        assert_eq!(row(&buf, 0), "0ab345");
+
+        buf.blit(4, 0, &src, src.area);
+        assert_eq!(row(&buf, 0), "0ab3ab");
+    }
+
+    #[test]
+    fn buffer_views() {
+        let mut buf = Buffer::new(Area::new(0, 0, 6, 3));
+        let mut view = buf.view_mut(Area::new(2, 1, 3, 2));
+        assert_eq!(view.area(), Area::new(0, 0, 3, 2));
+
+        view.set_string(1, 0, "hello", Style::default());
+        view.set_string(0, 5, "hidden", Style::default());
+        assert!(view.get_mut(3, 0).is_none());
+        view.view_mut(Area::new(1, 1, 5, 5)).set_string(0, 0, "!!!", Style::default());
+
+        assert_eq!(row(&buf, 0), "      ");
+        assert_eq!(row(&buf, 1), "   he ");
+        assert_eq!(row(&buf, 2), "   !! ");
+    }
+
+    #[test]
+    fn view_blitting() {
+        let mut src = Buffer::new(Area::new(0, 0, 4, 2));
+        src.set_string(0, 1, "a界b", Style::default());
+
+        // The wide grapheme is cut off by the edge of the view, so it's cleared.
+        let mut buf = Buffer::new(Area::new(0, 0, 5, 1));
+        buf.view_mut(Area::new(1, 0, 2, 1)).blit(0, 0, &src, Area::new(0, 1, 4, 1));
+        assert_eq!(row(&buf, 0), " a   ");
+    }
+
+    #[test]
+    fn rendering_widgets_into_views() {
+        let mut buf = Buffer::new(Area::new(0, 0, 7, 4));
+        let mut view = buf.view_mut(Area::new(1, 1, 4, 3));
+
+        // The outline is bigger than the view, so its right and bottom edges are clipped.
+        Rectangle::new(Style::default()).render(Area::new(0, 0, 6, 6), &mut view);
+
+        let mut content = ScrollView::new(6, 1);
+        content.buffer_mut().set_string(0, 0, "abcdef", Style::default());
+        content.offset = (2, 0);
+        content.render(Area::new(1, 1, 3, 1), &mut view);
+
+        assert_eq!(row(&buf, 0), "       ");
+        assert_eq!(row(&buf, 1), " ┌───  ");
+        assert_eq!(row(&buf, 2), " │cde  ");
+        assert_eq!(row(&buf, 3), " │     ");
+    }
+
+    #[test]
+    fn region_styling() {
+        let mut buf = Buffer::new(Area::new(0, 0, 4, 2));
+        buf.set_string(0, 0, "abcd", Style::default().fg(Color::Red));
+        buf.set_style(Area::new(1, 0, 8, 1), Style::default().bg(Color::Blue));
+        buf.view_mut(Area::new(0, 1, 4, 1)).set_style(Area::new(3, 0, 4, 4), Color::Green);
+
+        let colors = buf.content.iter().map(|cell| (cell.fg, cell.bg)).collect::<Vec<_>>();
+        assert_eq!(colors[..4], [
+            (Color::Red, Color::Reset),
+            (Color::Red, Color::Blue),
+            (Color::Red, Color::Blue),
+            (Color::Red, Color::Blue),
+        ]);
+        assert_eq!(colors[7], (Color::Green, Color::Reset));
+        assert_eq!(row(&buf, 0), "abcd");
+    }
+
+    #[test]
+    fn hyperlinks() {
+        let previous = Buffer::new(Area::new(0, 0, 6, 1));
+        let mut next = previous.clone();
+        assert_eq!(next.set_hyperlink(1, 0, "a界", "https://a.b", Style::default()), (4, 0));
+        let links = next.content.iter().map(|cell| cell.hyperlink()).collect::<Vec<_>>();
+        let link = Some("https://a.b");
+        assert_eq!(links, vec![None, link, link, link, None, None]);
+
+        // Changing only the target of a link still counts as a change.
+        let mut relinked = next.clone();
+        relinked.get_mut(1, 0).set_hyperlink(Some("https://c.d"));
+        let mut updates = Vec::new();
+        next.diff_into(&relinked, &mut updates);
+        assert_eq!(updates, vec![1]);
+
+        previous.diff_into(&next, &mut updates);
+        assert_eq!(updates, vec![1, 2]);
+    }
+
+    #[test]
+    fn damaged_row_diffing() {
+        let previous = Buffer::new(Area::new(0, 1, 3, 3));
+        let mut next = previous.clone();
+        next.set_string(0, 1, "a", Style::default());
+        next.set_string(1, 2, "b", Style::default());
+        next.set_string(2, 3, "c", Style::default());
+
+        let mut updates = Vec::new();
+        previous.diff_rows_into(&next, &[1..2, 3..9], &mut updates);
+        assert_eq!(updates, vec![0, 8]);
+        previous.diff_into(&next, &mut updates);
+        assert_eq!(updates, vec![0, 4, 8]);
     }
 }
@@ -10,7 +10,7 @@ use super::Style;
 
 
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Cell {
     pub(crate) symbol: CompactString,
     /// The foreground color for the cell.
@@ -19,6 +19,9 @@ pub struct Cell {
     pub bg: Color,
     /// The modifier for the cell.
     pub modifier: Modifier,
+    /// The color of the cell's underline. [`Color::Reset`] uses the foreground color.
+    pub underline_color: Color,
+    pub(crate) hyperlink: Option<CompactString>,
 }
 
 impl Default for Cell {
@@ -31,6 +34,10 @@ impl Cell {
     /// An empty cell.
     pub const EMPTY: Self = Self::new(" ");
 
+    /// A cell with no symbol, which lets whatever is below it show through when drawn on a
+    /// [`Layer`](crate::Layer).
+    pub const TRANSPARENT: Self = Self::new("");
+
     /// Create a new cell with the given content.
     pub const fn new(content: &'static str) -> Self {
         Self {
@@ -38,6 +45,8 @@ impl Cell {
             fg: Color::Reset,
             bg: Color::Reset,
             modifier: Modifier::empty(),
+            underline_color: Color::Reset,
+            hyperlink: None,
         }
     }
 
@@ -65,6 +74,35 @@ impl Cell {
         self
     }
 
+    /// Get the target of the hyperlink this cell is part of, if it has one.
+    pub fn hyperlink(&self) -> Option<&str> {
+        self.hyperlink.as_deref()
+    }
+
+    /// Make this cell part of a hyperlink to the given target (usually a URL), or remove its
+    /// hyperlink with `None`.
+    ///
+    /// Terminals that support hyperlinks (with OSC 8) let users open adjacent cells that share a
+    /// target as a single link. Terminals that don't simply show the cell's content.
+    pub fn set_hyperlink(&mut self, target: Option<&str>) -> &mut Self {
+        self.hyperlink = target.map(CompactString::new);
+        self
+    }
+
+    /// Get this cell's style.
+    ///
+    /// The style sets every color and modifier, so applying it to another cell makes that cell
+    /// look exactly like this one (apart from the symbol and hyperlink).
+    pub fn style(&self) -> Style {
+        Style {
+            fg: Some(self.fg),
+            bg: Some(self.bg),
+            underline_color: Some(self.underline_color),
+            add_modifier: self.modifier,
+            sub_modifier: Modifier::all().difference(self.modifier),
+        }
+    }
+
     /// Set the style for this cell.
     ///
     /// `style` accepts any type that is convertible to a [`Style`] object
@@ -77,6 +115,9 @@ impl Cell {
         if let Some(c) = style.bg {
             self.bg = c;
         }
+        if let Some(c) = style.underline_color {
+            self.underline_color = c;
+        }
         self.modifier.insert(style.add_modifier);
         self.modifier.remove(style.sub_modifier);
         self
@@ -88,5 +129,7 @@ impl Cell {
         self.fg = Color::Reset;
         self.bg = Color::Reset;
         self.modifier = Modifier::empty();
+        self.underline_color = Color::Reset;
+        self.hyperlink = None;
     }
 }
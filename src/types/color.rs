@@ -70,52 +70,441 @@ impl Color {
 
 
 
+/// The standard xterm palette used for the 16 named ANSI colors.
+const ANSI_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl Color {
+    /// Get this color's red, green, and blue channels.
+    ///
+    /// Named and [`Color::Ansi`] colors are resolved using the standard xterm palette, although
+    /// most terminals let their users customize the first 16 colors. [`Color::Reset`] has no
+    /// channels, since it depends entirely on the terminal.
+    pub const fn to_rgb(self) -> Option<(u8, u8, u8)> {
+        let index = match self {
+            Self::Reset => return None,
+            Self::Rgb(r, g, b) => return Some((r, g, b)),
+            Self::Ansi(index) => index,
+            Self::Black => 0,
+            Self::Red => 1,
+            Self::Green => 2,
+            Self::Yellow => 3,
+            Self::Blue => 4,
+            Self::Magenta => 5,
+            Self::Cyan => 6,
+            Self::Gray => 7,
+            Self::DarkGray => 8,
+            Self::LightRed => 9,
+            Self::LightGreen => 10,
+            Self::LightYellow => 11,
+            Self::LightBlue => 12,
+            Self::LightMagenta => 13,
+            Self::LightCyan => 14,
+            Self::White => 15,
+        };
+        Some(match index {
+            0..=15 => ANSI_PALETTE[index as usize],
+            16..=231 => {
+                const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+                let i = index - 16;
+                (LEVELS[(i / 36) as usize], LEVELS[(i / 6 % 6) as usize], LEVELS[(i % 6) as usize])
+            }
+            _ => {
+                let level = 8 + 10 * (index - 232);
+                (level, level, level)
+            }
+        })
+    }
+
+    /// Get this color's hue, saturation, and lightness, using the same ranges as
+    /// [`Color::from_hsl`]. Returns `None` for [`Color::Reset`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dreg::Color;
+    ///
+    /// assert_eq!(Color::Rgb(255, 0, 0).to_hsl(), Some((0.0, 100.0, 50.0)));
+    /// ```
+    pub fn to_hsl(self) -> Option<(f64, f64, f64)> {
+        let (r, g, b) = self.to_rgb()?;
+        let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+        if max == min {
+            return Some((0.0, 0.0, lightness * 100.0));
+        }
+
+        let delta = max - min;
+        let saturation = if lightness > 0.5 {
+            delta / (2.0 - max - min)
+        } else {
+            delta / (max + min)
+        };
+        let hue = if max == r {
+            (g - b) / delta + if g < b { 6.0 } else { 0.0 }
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+        Some((hue * 60.0, saturation * 100.0, lightness * 100.0))
+    }
+
+    /// Mix this color with another one. An `alpha` of `0.0` gives this color, and `1.0` gives the
+    /// other one.
+    ///
+    /// If either color is [`Color::Reset`], the result is whichever color `alpha` is closer to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dreg::Color;
+    ///
+    /// let gray = Color::Rgb(0, 0, 0).blend(Color::Rgb(255, 255, 255), 0.5);
+    /// assert_eq!(gray, Color::Rgb(128, 128, 128));
+    /// ```
+    pub fn blend(self, other: Self, alpha: f64) -> Self {
+        let alpha = alpha.clamp(0.0, 1.0);
+        let (Some(from), Some(to)) = (self.to_rgb(), other.to_rgb()) else {
+            return if alpha < 0.5 { self } else { other };
+        };
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * alpha).round() as u8;
+        Self::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+    }
+
+    /// Make this color lighter by the given amount of lightness (out of 100).
+    ///
+    /// [`Color::Reset`] is returned unchanged.
+    pub fn lighten(self, amount: f64) -> Self {
+        self.map_hsl(|h, s, l| (h, s, l + amount))
+    }
+
+    /// Make this color darker by the given amount of lightness (out of 100).
+    ///
+    /// [`Color::Reset`] is returned unchanged.
+    pub fn darken(self, amount: f64) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Make this color more saturated by the given amount of saturation (out of 100). Negative
+    /// amounts desaturate it.
+    ///
+    /// [`Color::Reset`] is returned unchanged.
+    pub fn saturate(self, amount: f64) -> Self {
+        self.map_hsl(|h, s, l| (h, s + amount, l))
+    }
+
+    /// This color's relative luminance, as defined by WCAG 2, from `0.0` (black) to `1.0`
+    /// (white). Returns `None` for [`Color::Reset`].
+    pub fn luminance(self) -> Option<f64> {
+        let (r, g, b) = self.to_rgb()?;
+        let linear = |c: u8| {
+            let c = c as f64 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+    }
+
+    /// The WCAG 2 contrast ratio between this color and another one, from `1.0` (no contrast) to
+    /// `21.0` (black on white). Returns `None` if either color is [`Color::Reset`].
+    ///
+    /// WCAG recommends a ratio of at least `4.5` for normal text.
+    pub fn contrast_ratio(self, other: Self) -> Option<f64> {
+        let a = self.luminance()?;
+        let b = other.luminance()?;
+        Some((a.max(b) + 0.05) / (a.min(b) + 0.05))
+    }
+
+    fn map_hsl(self, f: impl FnOnce(f64, f64, f64) -> (f64, f64, f64)) -> Self {
+        match self.to_hsl() {
+            Some((h, s, l)) => {
+                let (h, s, l) = f(h, s, l);
+                Self::from_hsl(h, s, l)
+            }
+            None => self,
+        }
+    }
+}
+
+/// Formats colors as hex strings (`#rrggbb`), resolving named and [`Color::Ansi`] colors with
+/// [`Color::to_rgb`]. [`Color::Reset`] is formatted as `reset`.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_rgb() {
+            Some((r, g, b)) => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+            None => write!(f, "reset"),
+        }
+    }
+}
+
+
+
 /// Error type indicating a failure to parse a color string.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub struct ParseColorError;
+pub struct ParseColorError {
+    kind: ParseColorErrorKind,
+    position: usize,
+}
+
+impl ParseColorError {
+    fn new(kind: ParseColorErrorKind, position: usize) -> Self {
+        Self { kind, position }
+    }
+
+    /// What was wrong with the color string.
+    pub fn kind(&self) -> ParseColorErrorKind {
+        self.kind
+    }
+
+    /// The byte offset in the color string where the problem was found.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// The ways a color string can fail to parse.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ParseColorErrorKind {
+    /// The string was empty.
+    Empty,
+    /// The string wasn't a known color name or function.
+    UnknownName,
+    /// A hex color didn't have 3, 6, or 8 digits.
+    InvalidHexLength,
+    /// A hex color with an alpha channel wasn't fully opaque, which can't be represented.
+    Transparent,
+    /// A character that doesn't belong in a number was found.
+    InvalidDigit,
+    /// A number was expected, but wasn't found.
+    ExpectedNumber,
+    /// A number was outside of the range allowed for it.
+    OutOfRange,
+    /// A function like `rgb(...)` had the wrong number of arguments.
+    ArgumentCount {
+        /// The number of arguments the function takes.
+        expected: usize,
+        /// The number of arguments that were found.
+        found: usize,
+    },
+    /// A function like `rgb(...)` was missing its closing parenthesis, or had something after it.
+    UnclosedParenthesis,
+}
 
 impl fmt::Display for ParseColorError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "failed to parse color")
+        match self.kind {
+            ParseColorErrorKind::Empty => write!(f, "empty color string")?,
+            ParseColorErrorKind::UnknownName => write!(f, "unknown color name")?,
+            ParseColorErrorKind::InvalidHexLength => {
+                write!(f, "hex colors must have 3, 6, or 8 digits")?
+            }
+            ParseColorErrorKind::Transparent => write!(f, "transparent colors are not supported")?,
+            ParseColorErrorKind::InvalidDigit => write!(f, "invalid digit")?,
+            ParseColorErrorKind::ExpectedNumber => write!(f, "expected a number")?,
+            ParseColorErrorKind::OutOfRange => write!(f, "number out of range")?,
+            ParseColorErrorKind::ArgumentCount { expected, found } => {
+                write!(f, "expected {expected} arguments, found {found}")?
+            }
+            ParseColorErrorKind::UnclosedParenthesis => write!(f, "expected ')' at the end")?,
+        }
+        write!(f, " at position {}", self.position)
     }
 }
 
 impl std::error::Error for ParseColorError {}
 
 /// Converts a string representation to a `Color` instance.
+///
+/// The following formats are accepted (ignoring case and surrounding whitespace):
+///
+/// - Named colors, like `red`, `light-blue`, `light_blue`, `dark gray`, or `reset`.
+/// - Hex colors, like `#abc`, `#aabbcc`, or `#aabbccff` (the alpha channel must be `ff`).
+/// - `rgb(r, g, b)`, with each channel from 0 to 255.
+/// - `hsl(h, s%, l%)`, with the same ranges as [`Color::from_hsl`] (the `%` signs are optional).
+/// - ANSI palette indices, like `ansi(208)` or just `208`.
+///
+/// # Examples
+///
+/// ```
+/// use dreg::Color;
+///
+/// assert_eq!("light-blue".parse(), Ok(Color::LightBlue));
+/// assert_eq!("#abc".parse(), Ok(Color::Rgb(0xaa, 0xbb, 0xcc)));
+/// assert_eq!("rgb(10, 20, 30)".parse(), Ok(Color::Rgb(10, 20, 30)));
+/// assert_eq!("208".parse(), Ok(Color::Ansi(208)));
+///
+/// let error = "rgb(10, 2o, 30)".parse::<Color>().unwrap_err();
+/// assert_eq!(error.to_string(), "invalid digit at position 9");
+/// ```
 impl FromStr for Color {
     type Err = ParseColorError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(rgba) = parse_hex_color(s) {
-            Ok(Self::Rgb(rgba[0], rgba[1], rgba[2]))
+        let start = s.len() - s.trim_start().len();
+        let input = s.trim();
+        if input.is_empty() {
+            return Err(ParseColorError::new(ParseColorErrorKind::Empty, 0));
+        }
+
+        if let Some(hex) = input.strip_prefix('#') {
+            parse_hex_color(hex, start + 1)
+        } else if input.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(Self::Ansi(parse_integer(input, start, 255)? as u8))
+        } else if let Some(open) = input.find('(') {
+            parse_color_function(&input[..open], &input[open + 1..], start, open + 1)
         } else {
-            return Err(ParseColorError);
+            parse_color_name(input)
+                .ok_or(ParseColorError::new(ParseColorErrorKind::UnknownName, start))
         }
     }
 }
 
-fn parse_hex_color(input: &str) -> Option<[u8; 4]> {
-    if !input.starts_with('#') {
-        return None;
+fn parse_color_name(name: &str) -> Option<Color> {
+    let name = name.to_ascii_lowercase().replace(['-', '_', ' '], "");
+    Some(match name.as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    })
+}
+
+/// Parse the digits of a hex color, which start at byte `position` of the original string.
+fn parse_hex_color(hex: &str, position: usize) -> Result<Color, ParseColorError> {
+    if let Some(i) = hex.find(|c: char| !c.is_ascii_hexdigit()) {
+        return Err(ParseColorError::new(ParseColorErrorKind::InvalidDigit, position + i));
     }
-    match input.len() {
-        7 => {
-            let r = u8::from_str_radix(input.get(1..3)?, 16).ok()?;
-            let g = u8::from_str_radix(input.get(3..5)?, 16).ok()?;
-            let b = u8::from_str_radix(input.get(5..7)?, 16).ok()?;
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).unwrap_or_default();
+    let byte = |i: usize| digit(i) * 16 + digit(i + 1);
+    match hex.len() {
+        3 => Ok(Color::Rgb(digit(0) * 17, digit(1) * 17, digit(2) * 17)),
+        6 => Ok(Color::Rgb(byte(0), byte(2), byte(4))),
+        8 if byte(6) == 255 => Ok(Color::Rgb(byte(0), byte(2), byte(4))),
+        8 => Err(ParseColorError::new(ParseColorErrorKind::Transparent, position + 6)),
+        _ => Err(ParseColorError::new(ParseColorErrorKind::InvalidHexLength, position)),
+    }
+}
 
-            Some([r, g, b, 255])
+/// Parse a function like `rgb(...)`, where `args` is everything after the opening parenthesis.
+/// `start` is the byte offset of `name` in the original string, and `args` starts `offset` bytes
+/// after it.
+fn parse_color_function(
+    name: &str,
+    args: &str,
+    start: usize,
+    offset: usize,
+) -> Result<Color, ParseColorError> {
+    let Some(args) = args.strip_suffix(')') else {
+        return Err(ParseColorError::new(
+            ParseColorErrorKind::UnclosedParenthesis,
+            start + offset + args.len(),
+        ));
+    };
+
+    let mut values = Vec::with_capacity(3);
+    let mut position = start + offset;
+    for arg in args.split(',') {
+        let trimmed = arg.trim_start();
+        values.push((trimmed.trim_end(), position + arg.len() - trimmed.len()));
+        position += arg.len() + 1;
+    }
+    let expect = |expected: usize| {
+        if values.len() == expected {
+            Ok(())
+        } else {
+            let kind = ParseColorErrorKind::ArgumentCount { expected, found: values.len() };
+            Err(ParseColorError::new(kind, start + offset))
         }
-        9 => {
-            let r = u8::from_str_radix(input.get(1..3)?, 16).ok()?;
-            let g = u8::from_str_radix(input.get(3..5)?, 16).ok()?;
-            let b = u8::from_str_radix(input.get(5..7)?, 16).ok()?;
-            let a = u8::from_str_radix(input.get(7..9)?, 16).ok()?;
+    };
 
-            Some([r, g, b, a])
+    match name.trim().to_ascii_lowercase().as_str() {
+        "rgb" => {
+            expect(3)?;
+            let mut channels = [0; 3];
+            for (channel, (value, position)) in channels.iter_mut().zip(&values) {
+                *channel = parse_integer(value, *position, 255)? as u8;
+            }
+            Ok(Color::Rgb(channels[0], channels[1], channels[2]))
         }
-        _ => None,
+        "hsl" => {
+            expect(3)?;
+            let h = parse_decimal(values[0].0, values[0].1, 360.0)?;
+            let s = parse_decimal(values[1].0, values[1].1, 100.0)?;
+            let l = parse_decimal(values[2].0, values[2].1, 100.0)?;
+            Ok(Color::from_hsl(h, s, l))
+        }
+        "ansi" => {
+            expect(1)?;
+            Ok(Color::Ansi(parse_integer(values[0].0, values[0].1, 255)? as u8))
+        }
+        _ => Err(ParseColorError::new(ParseColorErrorKind::UnknownName, start)),
+    }
+}
+
+/// Parse a whole number no greater than `max`, found at byte `position` of the original string.
+fn parse_integer(value: &str, position: usize, max: u32) -> Result<u32, ParseColorError> {
+    if value.is_empty() {
+        return Err(ParseColorError::new(ParseColorErrorKind::ExpectedNumber, position));
+    }
+    if let Some(i) = value.find(|c: char| !c.is_ascii_digit()) {
+        return Err(ParseColorError::new(ParseColorErrorKind::InvalidDigit, position + i));
+    }
+    match value.parse::<u32>() {
+        Ok(n) if n <= max => Ok(n),
+        _ => Err(ParseColorError::new(ParseColorErrorKind::OutOfRange, position)),
+    }
+}
+
+/// Parse a number from 0 to `max`, with an optional fraction and `%` sign, found at byte
+/// `position` of the original string.
+fn parse_decimal(value: &str, position: usize, max: f64) -> Result<f64, ParseColorError> {
+    let number = value.strip_suffix('%').unwrap_or(value);
+    if number.is_empty() {
+        return Err(ParseColorError::new(ParseColorErrorKind::ExpectedNumber, position));
+    }
+    if let Some(i) = number.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        return Err(ParseColorError::new(ParseColorErrorKind::InvalidDigit, position + i));
+    }
+    match number.parse::<f64>() {
+        Ok(n) if n <= max => Ok(n),
+        Ok(_) => Err(ParseColorError::new(ParseColorErrorKind::OutOfRange, position)),
+        Err(_) => Err(ParseColorError::new(ParseColorErrorKind::InvalidDigit, position)),
     }
 }
 
@@ -227,3 +616,87 @@ fn hue_to_rgb(p: f64, q: f64, t: f64) -> f64 {
         p
     }
 }
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_conversions() {
+        assert_eq!(Color::Reset.to_rgb(), None);
+        assert_eq!(Color::LightBlue.to_rgb(), Some((92, 92, 255)));
+        assert_eq!(Color::Ansi(12).to_rgb(), Color::LightBlue.to_rgb());
+        assert_eq!(Color::Ansi(196).to_rgb(), Some((255, 0, 0)));
+        assert_eq!(Color::Ansi(67).to_rgb(), Some((95, 135, 175)));
+        assert_eq!(Color::Ansi(255).to_rgb(), Some((238, 238, 238)));
+
+        for color in [Color::Rgb(43, 43, 51), Color::Rgb(12, 200, 99), Color::Rgb(250, 5, 180)] {
+            let (h, s, l) = color.to_hsl().unwrap();
+            assert_eq!(Color::from_hsl(h, s, l), color);
+            assert_eq!(color.to_string().parse::<Color>(), Ok(color));
+        }
+        assert_eq!(Color::Red.to_string(), "#cd0000");
+        assert_eq!(Color::Reset.to_string().parse::<Color>(), Ok(Color::Reset));
+        assert_eq!(Color::Reset.to_string(), "reset");
+    }
+
+    #[test]
+    fn color_parsing() {
+        let parse = |s: &str| s.parse::<Color>();
+        assert_eq!(parse("red"), Ok(Color::Red));
+        assert_eq!(parse(" Light_Blue "), Ok(Color::LightBlue));
+        assert_eq!(parse("dark grey"), Ok(Color::DarkGray));
+        assert_eq!(parse("#AbC"), Ok(Color::Rgb(0xaa, 0xbb, 0xcc)));
+        assert_eq!(parse("#0a141eff"), Ok(Color::Rgb(10, 20, 30)));
+        assert_eq!(parse("RGB(10,20 , 30)"), Ok(Color::Rgb(10, 20, 30)));
+        assert_eq!(parse("hsl(210, 50%, 40%)"), Ok(Color::from_hsl(210.0, 50.0, 40.0)));
+        assert_eq!(parse("hsl(0, 0, 100)"), Ok(Color::Rgb(255, 255, 255)));
+        assert_eq!(parse("ansi( 208 )"), Ok(Color::Ansi(208)));
+        assert_eq!(parse("208"), Ok(Color::Ansi(208)));
+
+        let error = |s: &str| {
+            let error = parse(s).unwrap_err();
+            (error.kind(), error.position())
+        };
+        assert_eq!(error("  "), (ParseColorErrorKind::Empty, 0));
+        assert_eq!(error(" purple"), (ParseColorErrorKind::UnknownName, 1));
+        assert_eq!(error("#12345"), (ParseColorErrorKind::InvalidHexLength, 1));
+        assert_eq!(error("#12g"), (ParseColorErrorKind::InvalidDigit, 3));
+        assert_eq!(error("#11223380"), (ParseColorErrorKind::Transparent, 7));
+        assert_eq!(error("#0a141e00"), (ParseColorErrorKind::Transparent, 7));
+        assert_eq!(error("256"), (ParseColorErrorKind::OutOfRange, 0));
+        assert_eq!(error("rgb(1, 2)"), (
+            ParseColorErrorKind::ArgumentCount { expected: 3, found: 2 },
+            4,
+        ));
+        assert_eq!(error("rgb(1, , 3)"), (ParseColorErrorKind::ExpectedNumber, 7));
+        assert_eq!(error("rgb(1, 2, 300)"), (ParseColorErrorKind::OutOfRange, 10));
+        assert_eq!(error("hsl(10, 5x%, 3)"), (ParseColorErrorKind::InvalidDigit, 9));
+        assert_eq!(error("ansi(3"), (ParseColorErrorKind::UnclosedParenthesis, 6));
+        assert_eq!(error("cmyk(1)"), (ParseColorErrorKind::UnknownName, 0));
+        assert_eq!(
+            parse("hsl(400, 0, 0)").unwrap_err().to_string(),
+            "number out of range at position 4",
+        );
+    }
+
+    #[test]
+    fn color_manipulation() {
+        let base = Color::from_hsl(210.0, 50.0, 40.0);
+        assert_eq!(base.lighten(20.0), Color::from_hsl(210.0, 50.0, 60.0));
+        assert_eq!(base.darken(50.0), Color::Rgb(0, 0, 0));
+        assert_eq!(base.saturate(-100.0), Color::from_hsl(0.0, 0.0, 40.0));
+        assert_eq!(Color::Reset.lighten(10.0), Color::Reset);
+
+        assert_eq!(Color::Blue.blend(Color::Reset, 0.2), Color::Blue);
+        let blended = Color::Rgb(10, 20, 30).blend(Color::Rgb(30, 20, 10), 0.25);
+        assert_eq!(blended, Color::Rgb(15, 20, 25));
+
+        let ratio = Color::Rgb(0, 0, 0).contrast_ratio(Color::Rgb(255, 255, 255)).unwrap();
+        assert!((ratio - 21.0).abs() < 1e-9);
+        assert_eq!(Color::Gray.contrast_ratio(Color::Gray), Some(1.0));
+        assert_eq!(Color::Gray.contrast_ratio(Color::Reset), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Damage type
+
+
+
+use std::ops::Range;
+
+use super::Area;
+
+
+
+/// The regions of a frame that need to be redrawn.
+///
+/// When a [`Program`](crate::Program) opts into retained rendering, everything it draws is kept
+/// between frames, and only the damaged regions are redrawn and compared against what's on the
+/// screen. Mark regions as damaged with [`Frame::mark_dirty`](crate::Frame::mark_dirty).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Damage {
+    areas: Vec<Area>,
+    full: bool,
+}
+
+impl Damage {
+    /// Damage that covers everything.
+    pub fn full() -> Self {
+        Self {
+            areas: Vec::new(),
+            full: true,
+        }
+    }
+
+    /// Mark the given area as damaged.
+    pub fn add(&mut self, area: Area) {
+        if !self.full && area.w > 0 && area.h > 0 {
+            self.areas.push(area);
+        }
+    }
+
+    /// Mark everything as damaged.
+    pub fn add_all(&mut self) {
+        self.full = true;
+        self.areas.clear();
+    }
+
+    /// Whether everything is damaged.
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    /// Whether nothing is damaged.
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.areas.is_empty()
+    }
+
+    /// The areas marked as damaged. This is empty if everything is damaged.
+    pub fn areas(&self) -> &[Area] {
+        &self.areas
+    }
+
+    /// Whether any part of the given area is damaged.
+    pub fn intersects(&self, area: Area) -> bool {
+        self.full || self.areas.iter().any(|damaged| {
+            let overlap = damaged.intersection(area);
+            overlap.w > 0 && overlap.h > 0
+        })
+    }
+
+    /// Remove all damage.
+    pub fn clear(&mut self) {
+        self.full = false;
+        self.areas.clear();
+    }
+
+    /// The sorted, non-overlapping ranges of rows within `bounds` that contain damage.
+    pub fn rows(&self, bounds: Area) -> Vec<Range<u16>> {
+        if self.full {
+            return std::iter::once(bounds.top()..bounds.bottom()).collect();
+        }
+        let mut rows = self.areas.iter()
+            .map(|area| area.intersection(bounds))
+            .filter(|area| area.w > 0 && area.h > 0)
+            .map(|area| area.top()..area.bottom())
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<u16>> = Vec::with_capacity(rows.len());
+        for range in rows {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damaged_rows() {
+        let bounds = Area::new(0, 0, 10, 10);
+        let mut damage = Damage::default();
+        assert!(damage.is_empty());
+        assert!(!damage.intersects(bounds));
+
+        damage.add(Area::new(2, 6, 3, 2));
+        damage.add(Area::new(0, 1, 1, 2));
+        damage.add(Area::new(5, 2, 1, 3));
+        damage.add(Area::new(5, 8, 0, 2));
+        damage.add(Area::new(0, 9, 20, 20));
+        assert_eq!(damage.rows(bounds), vec![1..5, 6..8, 9..10]);
+        assert!(damage.intersects(Area::new(4, 7, 5, 5)));
+        assert!(!damage.intersects(Area::new(5, 6, 5, 2)));
+
+        damage.add_all();
+        assert_eq!(damage.rows(bounds), std::iter::once(0..10).collect::<Vec<_>>());
+        damage.clear();
+        assert!(damage.rows(bounds).is_empty());
+    }
+}
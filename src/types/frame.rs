@@ -2,7 +2,9 @@
 
 
 
-use super::{Area, Buffer, Command};
+use std::time::{Duration, Instant};
+
+use super::{Area, Buffer, Command, Damage, Layer, Layers, Style, Theme};
 
 
 
@@ -12,17 +14,122 @@ pub struct Frame<'a> {
     /// The height of the frame, in cells.
     pub rows: u16,
     /// The frame's [`Buffer`].
+    ///
+    /// With retained rendering, this starts out holding the previous frame's contents.
     pub buffer: &'a mut Buffer,
+    /// The regions of this frame that need to be redrawn.
+    pub damage: &'a mut Damage,
+    /// The [`Layer`]s drawn above the frame's buffer at the end of this frame.
+    pub layers: &'a mut Layers,
+    /// The [`Theme`] that widgets resolve their styles from. Changes to it persist between frames.
+    pub theme: &'a mut Theme,
     /// A set of [`Command`]s to be processed at the end of this frame.
     pub commands: &'a mut Vec<Command>,
     pub cursor: Option<(u16, u16)>,
     /// Flag to indicate whether the platform should safely exit at the end of this frame.
     pub should_exit: bool,
+    /// When the next frame should be rendered, if the program uses
+    /// [on-demand rendering](crate::RenderMode::OnDemand) and it shouldn't wait for input.
+    pub next_redraw: Option<Instant>,
+    /// When this frame started rendering.
+    ///
+    /// Anything that changes over time should use this rather than [`Instant::now`], so that
+    /// everything in the frame moves in step.
+    pub time: Instant,
 }
 
 impl<'a> Frame<'a> {
+    /// How long the platform waits between frames when rendering continuously, and between the
+    /// frames requested with [`Frame::request_animation_frame`].
+    pub const ANIMATION_INTERVAL: Duration = Duration::from_millis(31);
+
+    /// Create a new frame that renders into the given buffer, taking its size from the buffer's
+    /// area, with no cursor and no redraw requested.
+    pub fn new(
+        buffer: &'a mut Buffer,
+        damage: &'a mut Damage,
+        layers: &'a mut Layers,
+        theme: &'a mut Theme,
+        commands: &'a mut Vec<Command>,
+        time: Instant,
+    ) -> Self {
+        Self {
+            cols: buffer.area.w,
+            rows: buffer.area.h,
+            buffer,
+            damage,
+            layers,
+            theme,
+            commands,
+            cursor: None,
+            should_exit: false,
+            next_redraw: None,
+            time,
+        }
+    }
+
     /// Get this frame's [`Area`].
     pub fn area(&self) -> Area {
         self.buffer.area
     }
+
+    /// Resolve the style for the given role from this frame's [`Theme`].
+    pub fn style(&self, role: &str) -> Style {
+        self.theme.style(role)
+    }
+
+    /// Mark the given area as needing to be redrawn, and clear it.
+    ///
+    /// This only matters when the program uses
+    /// [retained rendering](crate::Program::retained_rendering), since otherwise the whole frame
+    /// is redrawn every time. Only the rows covered by dirty areas are compared with what's on
+    /// the screen, so anything drawn outside of them won't show up until they're marked dirty.
+    pub fn mark_dirty(&mut self, area: Area) {
+        self.buffer.view_mut(area).reset();
+        self.damage.add(area.intersection(self.buffer.area));
+    }
+
+    /// Whether any part of the given area needs to be redrawn this frame.
+    ///
+    /// Widgets can use this to skip rendering regions that haven't changed since the last frame.
+    pub fn is_dirty(&self, area: Area) -> bool {
+        self.damage.intersects(area)
+    }
+
+    /// Render another frame as soon as possible, even if there's no new input.
+    ///
+    /// This does nothing for programs that render [continuously](crate::RenderMode::Continuous).
+    pub fn request_redraw(&mut self) {
+        self.request_redraw_at(self.time);
+    }
+
+    /// Render another frame once the given amount of time has passed, even if there's no new
+    /// input. If several redraws are requested, the earliest one is used.
+    ///
+    /// The delay is measured from [`Frame::time`], like everything else in the frame.
+    ///
+    /// This does nothing for programs that render [continuously](crate::RenderMode::Continuous).
+    pub fn request_redraw_after(&mut self, delay: Duration) {
+        self.request_redraw_at(self.time + delay);
+    }
+
+    /// Render the next frame of an animation, one [`Frame::ANIMATION_INTERVAL`] after this one.
+    ///
+    /// This does nothing for programs that render [continuously](crate::RenderMode::Continuous).
+    pub fn request_animation_frame(&mut self) {
+        self.request_redraw_at(self.time + Self::ANIMATION_INTERVAL);
+    }
+
+    /// Render another frame at the given time, even if there's no new input. If several redraws
+    /// are requested, the earliest one is used.
+    pub fn request_redraw_at(&mut self, at: Instant) {
+        self.next_redraw = Some(self.next_redraw.map_or(at, |next| next.min(at)));
+    }
+
+    /// Get the [`Layer`] with the given name, creating it if it doesn't exist yet.
+    ///
+    /// Layers are drawn above the frame's buffer, from the lowest z-index to the highest.
+    pub fn layer(&mut self, name: &str, z: u16) -> &mut Layer {
+        self.layers.get(name, z)
+    }
 }
@@ -9,8 +9,15 @@ use super::Area;
 
 
 /// A utility object for managing your program's input state.
+#[derive(Default)]
 pub struct InputContext {
     keys_down: HashSet<Scancode>,
+    keys_pressed: Vec<KeyPress>,
+    text: String,
+    /// The modifier keys pressed since the last non-modifier key. Platforms report modifiers with
+    /// a press of the modifier key right before the key they apply to.
+    modifiers_pending: KeyPress,
+    mouse_presses: Vec<(MouseButton, bool)>,
     mouse_pos: Option<(u16, u16)>,
     latest_mouse_button: Option<(MouseButton, bool)>,
     resized: Option<(u16, u16)>,
@@ -18,23 +25,14 @@ pub struct InputContext {
     newly_unfocused: bool,
 }
 
-impl Default for InputContext {
-    fn default() -> Self {
-        Self {
-            keys_down: HashSet::new(),
-            mouse_pos: None,
-            latest_mouse_button: None,
-            resized: None,
-            newly_focused: false,
-            newly_unfocused: false,
-        }
-    }
-}
-
 impl InputContext {
     /// **IMPORTANT**: This function must be called at the end of *every* render pass.
     pub fn end_frame(&mut self) {
         self.resized = None;
+        self.keys_pressed.clear();
+        self.text.clear();
+        self.modifiers_pending = KeyPress::default();
+        self.mouse_presses.clear();
         self.newly_focused = false;
         self.newly_unfocused = false;
     }
@@ -44,18 +42,31 @@ impl InputContext {
         match input {
             Input::KeyDown(code) => {
                 let _repeat = self.handle_key_down(code);
+                if !self.modifiers_pending.set_modifier(code, true) {
+                    let press = KeyPress {
+                        code,
+                        ..std::mem::take(&mut self.modifiers_pending)
+                    };
+                    self.keys_pressed.push(press);
+                    if let Some(c) = press.to_char() {
+                        self.text.push(c);
+                    }
+                }
             }
             Input::KeyUp(code) => {
                 let _valid_keypress = self.handle_key_up(&code);
+                self.modifiers_pending.set_modifier(code, false);
             }
             Input::MouseMove(x, y) => {
                 self.mouse_pos = Some((x, y));
             }
             Input::MouseDown(button) => {
                 self.latest_mouse_button = Some((button, true));
+                self.mouse_presses.push((button, true));
             }
             Input::MouseUp(button) => {
                 self.latest_mouse_button = Some((button, false));
+                self.mouse_presses.push((button, false));
             }
             Input::Resize(x, y) => {
                 self.resized = Some((x, y));
@@ -77,7 +88,7 @@ impl InputContext {
     /// Shortcut for [`InputContext::handle_input`] with [`Input::KeyUp`],
     /// and the given [`Scancode`].
     pub fn handle_key_up(&mut self, code: &Scancode) -> bool {
-        self.keys_down.remove(&code)
+        self.keys_down.remove(code)
     }
 
     /// Get the currently pressed keys.
@@ -87,6 +98,19 @@ impl InputContext {
         &self.keys_down
     }
 
+    /// Get the keys pressed this frame, in order, along with the modifier keys held for each.
+    ///
+    /// Unlike [`InputContext::keys_down`], this includes repeated presses of the same key, and
+    /// doesn't include the modifier keys themselves.
+    pub fn keys_pressed(&self) -> &[KeyPress] {
+        &self.keys_pressed
+    }
+
+    /// Get the text typed this frame. Keys pressed while holding Ctrl or Alt don't type anything.
+    pub fn text_typed(&self) -> &str {
+        &self.text
+    }
+
     /// Get the current mouse position.
     pub fn mouse_pos(&self) -> Option<(u16, u16)> {
         self.mouse_pos
@@ -110,6 +134,26 @@ impl InputContext {
         self.keys_down.contains(scancode)
     }
 
+    /// Whether the given key was pressed this frame.
+    pub fn key_pressed(&self, scancode: Scancode) -> bool {
+        self.keys_pressed.iter().any(|press| press.code == scancode)
+    }
+
+    /// Whether the given mouse button was pressed this frame.
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_presses.contains(&(button, true))
+    }
+
+    /// Whether the given mouse button was released this frame.
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.mouse_presses.contains(&(button, false))
+    }
+
+    /// Whether the given mouse button is currently held down.
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.latest_mouse_button == Some((button, true))
+    }
+
     /// Akin to `area.hovered(&self)`.
     pub fn hovered(&self, area: &Area) -> bool {
         self.mouse_pos.is_some_and(|(x, y)| area.contains(x, y))
@@ -131,6 +175,42 @@ impl InputContext {
     }
 }
 
+/// A key press, along with the modifier keys held for it.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct KeyPress {
+    pub code: Scancode,
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl KeyPress {
+    /// Whether Ctrl or Alt was held, which makes this press a shortcut rather than typed text.
+    pub fn is_chord(&self) -> bool {
+        self.ctrl || self.alt
+    }
+
+    /// Get the character this press types, if it types one.
+    pub fn to_char(&self) -> Option<char> {
+        if self.is_chord() {
+            return None;
+        }
+        self.code.to_char(self.shift)
+    }
+
+    /// Update the flag for the given modifier key, returning whether it was a modifier key.
+    fn set_modifier(&mut self, code: Scancode, down: bool) -> bool {
+        let flag = match code {
+            Scancode::L_SHIFT | Scancode::R_SHIFT => &mut self.shift,
+            Scancode::L_CTRL | Scancode::R_CTRL => &mut self.ctrl,
+            Scancode::L_ALT | Scancode::R_ALT => &mut self.alt,
+            _ => return false,
+        };
+        *flag = down;
+        true
+    }
+}
+
 
 
 /// An input event.
@@ -332,6 +412,8 @@ impl Scancode {
             'X' => (Some(Self::L_SHIFT), Self::X),
             'y' => (None, Self::Y),
             'Y' => (Some(Self::L_SHIFT), Self::Y),
+            'z' => (None, Self::Z),
+            'Z' => (Some(Self::L_SHIFT), Self::Z),
 
             '1' => (None, Self::K_1),
             '!' => (Some(Self::L_SHIFT), Self::K_1),
@@ -383,6 +465,15 @@ impl Scancode {
             _ => (None, Self(0)),
         }
     }
+
+    /// Get the character this scancode types on a US keyboard layout, if it types one.
+    ///
+    /// This is the inverse of [`Scancode::from_char`], except that [`Scancode::ENTER`] and
+    /// [`Scancode::TAB`] aren't considered to type anything.
+    pub fn to_char(self, shift: bool) -> Option<char> {
+        let modifier = shift.then_some(Self::L_SHIFT);
+        (' '..='~').find(|c| Self::from_char(*c) == (modifier, self))
+    }
 }
 
 // Constants.
@@ -0,0 +1,234 @@
+//! Layer types
+
+
+
+use super::{Area, Buffer, Cell, Color, Modifier};
+
+
+
+/// A named [`Buffer`] that is drawn above the frame's main buffer.
+///
+/// Layers are useful for anything that needs to stack correctly over the rest of the program,
+/// like dialogs, dropdowns, and tooltips. Layer buffers start each frame fully transparent, so only
+/// the [`Cell`]s that are drawn to cover what's below them. Use [`Clear`](crate::Clear) to
+/// make a region opaque before drawing a popup into it.
+pub struct Layer {
+    name: String,
+    /// The layer's z-index. Layers with higher z-indices are drawn above those with lower ones.
+    pub z: u16,
+    /// The layer's contents.
+    pub buffer: Buffer,
+    /// Whether everything below this layer should be dimmed when the layer is drawn.
+    pub dim_below: bool,
+    /// The areas that cast a shadow onto what's below this layer.
+    pub shadows: Vec<Area>,
+    /// Whether this layer has been requested during the current frame.
+    active: bool,
+}
+
+impl Layer {
+    /// This layer's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Dim everything below this layer.
+    pub fn dim_below(&mut self) -> &mut Self {
+        self.dim_below = true;
+        self
+    }
+
+    /// Cast a shadow from the given area onto what's below this layer. The shadow falls one row
+    /// below, and two columns to the right of, the area.
+    pub fn shadow(&mut self, area: Area) -> &mut Self {
+        self.shadows.push(area);
+        self
+    }
+
+    fn begin(&mut self, area: Area) {
+        self.buffer.resize(area);
+        for cell in &mut self.buffer.content {
+            *cell = Cell::TRANSPARENT;
+        }
+        self.dim_below = false;
+        self.shadows.clear();
+        self.active = false;
+    }
+
+    fn draw(&self, target: &mut Buffer) {
+        if self.dim_below {
+            for cell in &mut target.content {
+                cell.modifier.insert(Modifier::DIM);
+            }
+        }
+        for shadow in &self.shadows {
+            let right = Area::new(shadow.right(), shadow.y + 1, 2, shadow.h);
+            let bottom = Area::new(shadow.x + 2, shadow.bottom(), shadow.w, 1);
+            for area in [right, bottom] {
+                let area = area.intersection(target.area);
+                for y in area.top()..area.bottom() {
+                    for x in area.left()..area.right() {
+                        let cell = target.get_mut(x, y);
+                        cell.fg = Color::DarkGray;
+                        cell.bg = Color::Black;
+                    }
+                }
+            }
+        }
+
+        let area = self.buffer.area.intersection(target.area);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = &self.buffer.content[self.buffer.index_of(x, y)];
+                if cell.symbol().is_empty() {
+                    // Transparent cells can still tint what's below them.
+                    let below = target.get_mut(x, y);
+                    if cell.fg != Color::Reset {
+                        below.fg = cell.fg;
+                    }
+                    if cell.bg != Color::Reset {
+                        below.bg = cell.bg;
+                    }
+                    below.modifier.insert(cell.modifier);
+                } else {
+                    *target.get_mut(x, y) = cell.clone();
+                }
+            }
+        }
+    }
+}
+
+
+
+/// The set of [`Layer`]s drawn above a frame's main [`Buffer`].
+///
+/// The layers are retained between frames so that their buffers can be reused, but only the
+/// layers requested during a frame (with [`Layers::get`] or [`Frame::layer`](crate::Frame::layer))
+/// are drawn.
+#[derive(Default)]
+pub struct Layers {
+    layers: Vec<Layer>,
+    area: Area,
+}
+
+impl Layers {
+    /// Get the layer with the given name, creating it with the given z-index if it doesn't exist.
+    ///
+    /// The z-index of an existing layer is updated to the given value.
+    pub fn get(&mut self, name: &str, z: u16) -> &mut Layer {
+        let index = match self.layers.iter().position(|layer| layer.name == name) {
+            Some(index) => index,
+            None => {
+                let mut layer = Layer {
+                    name: name.to_string(),
+                    z,
+                    buffer: Buffer::empty(),
+                    dim_below: false,
+                    shadows: Vec::new(),
+                    active: false,
+                };
+                layer.begin(self.area);
+                self.layers.push(layer);
+                self.layers.len() - 1
+            }
+        };
+        let layer = &mut self.layers[index];
+        layer.z = z;
+        layer.active = true;
+        layer
+    }
+
+    /// Clear all layers, and resize them to the given area. This should be called at the start of
+    /// every frame.
+    pub fn begin(&mut self, area: Area) {
+        self.area = area;
+        for layer in &mut self.layers {
+            layer.begin(area);
+        }
+    }
+
+    /// Whether any layers have been requested this frame.
+    pub fn any_active(&self) -> bool {
+        self.layers.iter().any(|layer| layer.active)
+    }
+
+    /// Draw every layer requested this frame onto the given buffer, from the lowest z-index to
+    /// the highest. Layers with the same z-index are drawn in the order they were created.
+    pub fn draw(&self, target: &mut Buffer) {
+        // The layers are stored in the order they were created, so a stable sort of them keeps
+        // that order for layers with the same z-index, even after their z-indices change.
+        let mut layers = self.layers.iter()
+            .filter(|layer| layer.active)
+            .collect::<Vec<_>>();
+        layers.sort_by_key(|layer| layer.z);
+        for layer in layers {
+            layer.draw(target);
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{buffer, row};
+
+    #[test]
+    fn layer_stacking() {
+        let mut base = buffer(4, 1);
+        base.set_string(0, 0, "base", Color::Red);
+
+        let mut layers = Layers::default();
+        layers.begin(base.area);
+        layers.get("top", 2).buffer.set_string(1, 0, "T", Color::Blue);
+        layers.get("middle", 1).buffer.set_string(1, 0, "MM", Color::Green);
+        layers.draw(&mut base);
+
+        assert_eq!(row(&base, 0), "bTMe");
+        assert_eq!(base.content[1].fg, Color::Blue);
+        assert_eq!(base.content[2].fg, Color::Green);
+
+        // Layers that aren't requested in a frame aren't drawn.
+        let mut base = buffer(4, 1);
+        base.set_string(0, 0, "base", Color::Red);
+        layers.begin(base.area);
+        layers.get("middle", 1).buffer.set_string(0, 0, "M", Color::Green);
+        layers.draw(&mut base);
+
+        assert_eq!(row(&base, 0), "Mase");
+    }
+
+    #[test]
+    fn layer_creation_order() {
+        let mut layers = Layers::default();
+        let mut frame = |first_z, second_z| {
+            let mut base = buffer(1, 1);
+            layers.begin(base.area);
+            layers.get("first", first_z).buffer.set_string(0, 0, "1", Color::Red);
+            layers.get("second", second_z).buffer.set_string(0, 0, "2", Color::Red);
+            layers.draw(&mut base);
+            row(&base, 0)
+        };
+
+        assert_eq!(frame(2, 1), "1");
+        // Once their z-indices are equal, the layer created last is drawn on top.
+        assert_eq!(frame(1, 1), "2");
+    }
+
+    #[test]
+    fn layer_backdrops() {
+        let mut base = buffer(5, 3);
+        let mut layers = Layers::default();
+        layers.begin(base.area);
+        layers.get("popup", 1)
+            .dim_below()
+            .shadow(Area::new(0, 0, 2, 1));
+        layers.draw(&mut base);
+
+        assert!(base.content.iter().all(|cell| cell.modifier.contains(Modifier::DIM)));
+        assert_eq!(base.content[base.index_of(2, 1)].bg, Color::Black);
+        assert_eq!(base.content[base.index_of(3, 1)].bg, Color::Black);
+        assert_eq!(base.content[base.index_of(1, 1)].bg, Color::Reset);
+    }
+}
@@ -27,9 +27,27 @@ bitflags::bitflags! {
         const REVERSED          = 0b0000_0100_0000;
         const HIDDEN            = 0b0000_1000_0000;
         const CROSSED_OUT       = 0b0001_0000_0000;
+        const DOUBLE_UNDERLINED = 0b0010_0000_0000;
+        const CURLY_UNDERLINED  = 0b0100_0000_0000;
+        const DOTTED_UNDERLINED = 0b1000_0000_0000;
+        const DASHED_UNDERLINED = 0b1_0000_0000_0000;
     }
 }
 
+impl Modifier {
+    /// Every kind of underline.
+    ///
+    /// Terminals can only draw one kind of underline at a time. If a cell has several, the first
+    /// one in the order `UNDERLINED`, `DOUBLE_UNDERLINED`, `CURLY_UNDERLINED`,
+    /// `DOTTED_UNDERLINED`, `DASHED_UNDERLINED` is used. Terminals that don't support the extended
+    /// kinds draw them as plain underlines.
+    pub const UNDERLINES: Self = Self::UNDERLINED
+        .union(Self::DOUBLE_UNDERLINED)
+        .union(Self::CURLY_UNDERLINED)
+        .union(Self::DOTTED_UNDERLINED)
+        .union(Self::DASHED_UNDERLINED);
+}
+
 /// Implement the `Debug` trait for `TextModifier` manually.
 ///
 /// This will avoid printing the empty modifier, and instead print it as 'NONE'.
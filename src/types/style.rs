@@ -10,39 +10,84 @@ use super::{Color, Modifier};
 pub struct Style {
     pub fg: Option<Color>,
     pub bg: Option<Color>,
+    pub underline_color: Option<Color>,
     pub add_modifier: Modifier,
     pub sub_modifier: Modifier,
 }
 
-impl Into<Style> for Color {
-    fn into(self) -> Style {
+impl From<Color> for Style {
+    fn from(color: Color) -> Self {
         Style {
-            fg: Some(self),
+            fg: Some(color),
             ..Default::default()
         }
     }
 }
 
-impl Into<Style> for (Color, Color) {
-    fn into(self) -> Style {
+impl From<(Color, Color)> for Style {
+    fn from((fg, bg): (Color, Color)) -> Self {
         Style {
-            fg: Some(self.0),
-            bg: Some(self.1),
+            fg: Some(fg),
+            bg: Some(bg),
             ..Default::default()
         }
     }
 }
 
-impl Into<Style> for Modifier {
-    fn into(self) -> Style {
+impl From<Modifier> for Style {
+    fn from(modifier: Modifier) -> Self {
         Style {
-            add_modifier: self,
+            add_modifier: modifier,
             ..Default::default()
         }
     }
 }
 
 impl Style {
+    /// Create a new style that leaves everything as it is.
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            underline_color: None,
+            add_modifier: Modifier::empty(),
+            sub_modifier: Modifier::empty(),
+        }
+    }
+
+    /// Create a new style that resets everything: the colors go back to [`Color::Reset`], and
+    /// every [`Modifier`] is removed.
+    ///
+    /// Patching another style on top of this one gives a style that looks the same no matter
+    /// what it's applied to.
+    pub const fn reset() -> Self {
+        Self {
+            fg: Some(Color::Reset),
+            bg: Some(Color::Reset),
+            underline_color: Some(Color::Reset),
+            add_modifier: Modifier::empty(),
+            sub_modifier: Modifier::all(),
+        }
+    }
+
+    /// Apply everything the other style sets on top of this one.
+    ///
+    /// Colors set by the other style replace this style's colors, and modifiers it adds or removes
+    /// override whatever this style does with them. Applying the patched style to a
+    /// [`Cell`](crate::Cell) has the same effect as applying this style and then the other one.
+    pub fn patch<S: Into<Style>>(self, other: S) -> Self {
+        let other = other.into();
+        let add_modifier = self.add_modifier.difference(other.sub_modifier);
+        let sub_modifier = self.sub_modifier.difference(other.add_modifier);
+        Self {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            underline_color: other.underline_color.or(self.underline_color),
+            add_modifier: add_modifier.union(other.add_modifier),
+            sub_modifier: sub_modifier.union(other.sub_modifier),
+        }
+    }
+
     /// Set the foreground [`Color`] for this style.
     pub fn fg(mut self, color: Color) -> Self {
         self.fg = Some(color);
@@ -55,6 +100,12 @@ impl Style {
         self
     }
 
+    /// Set the underline [`Color`] for this style.
+    pub fn underline_color(mut self, color: Color) -> Self {
+        self.underline_color = Some(color);
+        self
+    }
+
     /// Add a [`Modifier`] to this style.
     pub const fn add_modifier(mut self, modifier: Modifier) -> Self {
         self.sub_modifier = self.sub_modifier.difference(modifier);
@@ -89,6 +140,26 @@ impl Style {
         self.add_modifier(Modifier::UNDERLINED)
     }
 
+    /// Add [`Modifier::DOUBLE_UNDERLINED`] to this style, replacing any other kind of underline.
+    pub const fn double_underlined(self) -> Self {
+        self.remove_modifier(Modifier::UNDERLINES).add_modifier(Modifier::DOUBLE_UNDERLINED)
+    }
+
+    /// Add [`Modifier::CURLY_UNDERLINED`] to this style, replacing any other kind of underline.
+    pub const fn curly_underlined(self) -> Self {
+        self.remove_modifier(Modifier::UNDERLINES).add_modifier(Modifier::CURLY_UNDERLINED)
+    }
+
+    /// Add [`Modifier::DOTTED_UNDERLINED`] to this style, replacing any other kind of underline.
+    pub const fn dotted_underlined(self) -> Self {
+        self.remove_modifier(Modifier::UNDERLINES).add_modifier(Modifier::DOTTED_UNDERLINED)
+    }
+
+    /// Add [`Modifier::DASHED_UNDERLINED`] to this style, replacing any other kind of underline.
+    pub const fn dashed_underlined(self) -> Self {
+        self.remove_modifier(Modifier::UNDERLINES).add_modifier(Modifier::DASHED_UNDERLINED)
+    }
+
     /// Add [`Modifier::REVERSED`] to this style.
     pub const fn reversed(self) -> Self {
         self.add_modifier(Modifier::REVERSED)
@@ -104,3 +175,60 @@ impl Style {
         self.add_modifier(Modifier::CROSSED_OUT)
     }
 }
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cell;
+
+    #[test]
+    fn style_patching() {
+        let base = Style::new().fg(Color::Red).bold().remove_modifier(Modifier::ITALIC);
+        let overlay = Style::new().bg(Color::Blue).italic().remove_modifier(Modifier::BOLD);
+        assert_eq!(
+            base.patch(overlay),
+            Style::new()
+                .fg(Color::Red)
+                .bg(Color::Blue)
+                .italic()
+                .remove_modifier(Modifier::BOLD),
+        );
+
+        let mut patched = Cell::EMPTY;
+        patched.set_style(Style::new().underlined()).set_style(base.patch(overlay));
+        let mut layered = Cell::EMPTY;
+        layered.set_style(Style::new().underlined()).set_style(base).set_style(overlay);
+        assert_eq!(patched, layered);
+        assert_eq!(Style::reset().patch(Color::Red).add_modifier, Modifier::empty());
+    }
+
+    #[test]
+    fn cell_style_round_trip() {
+        let mut cell = Cell::EMPTY;
+        cell.set_style(Style::new().fg(Color::Green).underline_color(Color::Red).dim());
+
+        let mut copy = Cell::EMPTY;
+        copy.set_style(Style::new().bold()).set_style(cell.style());
+        assert_eq!(copy, cell);
+
+        copy.set_style(Style::reset());
+        assert_eq!(copy, Cell::EMPTY);
+    }
+
+    #[test]
+    fn underline_kinds() {
+        let kinds = [
+            (Style::new().double_underlined(), Modifier::DOUBLE_UNDERLINED),
+            (Style::new().curly_underlined(), Modifier::CURLY_UNDERLINED),
+            (Style::new().dotted_underlined(), Modifier::DOTTED_UNDERLINED),
+            (Style::new().dashed_underlined(), Modifier::DASHED_UNDERLINED),
+        ];
+        for (style, modifier) in kinds {
+            let style = Style::new().underlined().patch(style);
+            assert_eq!(style.add_modifier, modifier);
+            assert_eq!(style.sub_modifier, Modifier::UNDERLINES.difference(modifier));
+        }
+    }
+}
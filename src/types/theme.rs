@@ -0,0 +1,367 @@
+//! Theme type
+
+
+
+use std::collections::HashMap;
+
+use super::{Color, Style};
+
+
+
+/// A set of [`Style`]s for semantic roles, like `text`, `border.focused`, or `error`.
+///
+/// Roles form a hierarchy separated by dots, and each role inherits from its parent. Resolving
+/// `text.muted` starts with the style for `text`, and then applies whatever `text.muted` sets on
+/// top of it. Each role can also be overridden for the [`ThemeVariant::Light`] or
+/// [`ThemeVariant::Dark`] variant of the theme, and those overrides are applied at each level of
+/// the hierarchy after the shared style.
+///
+/// The default theme provides styles for `text`, `text.muted`, `border`, `border.focused`,
+/// `selection`, and `error`, in both variants. Use [`Theme::new`] to start from an empty theme.
+///
+/// ## Examples
+///
+/// ```rust
+/// use dreg::*;
+///
+/// let theme = Theme::new()
+///     .with_role("text", Color::White)
+///     .with_role("text.muted", Style::new().dim())
+///     .with_variant_role(ThemeVariant::Light, "text", Color::Black);
+///
+/// assert_eq!(theme.style("text.muted"), Style::new().fg(Color::White).dim());
+/// assert_eq!(
+///     theme.with_variant(ThemeVariant::Light).style("text.muted"),
+///     Style::new().fg(Color::Black).dim(),
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Theme {
+    roles: HashMap<String, Style>,
+    dark: HashMap<String, Style>,
+    light: HashMap<String, Style>,
+    /// The variant of the theme whose overrides are used when resolving styles.
+    pub variant: ThemeVariant,
+}
+
+/// Whether a [`Theme`] should use its light or dark overrides.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ThemeVariant {
+    /// Use the theme's dark overrides.
+    #[default]
+    Dark,
+    /// Use the theme's light overrides.
+    Light,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new()
+            .with_role("text", Style::new())
+            .with_role("text.muted", Style::new().dim())
+            .with_role("border", Style::new())
+            .with_role("border.focused", Style::new().bold())
+            .with_role("selection", Style::new().reversed())
+            .with_role("error", Style::new().bold())
+            .with_variant_role(ThemeVariant::Dark, "text", Color::Rgb(191, 191, 197))
+            .with_variant_role(ThemeVariant::Dark, "text.muted", Color::Rgb(113, 113, 127))
+            .with_variant_role(ThemeVariant::Dark, "border", Color::Rgb(73, 73, 83))
+            .with_variant_role(ThemeVariant::Dark, "border.focused", Color::Rgb(97, 175, 239))
+            .with_variant_role(ThemeVariant::Dark, "error", Color::Rgb(224, 108, 117))
+            .with_variant_role(ThemeVariant::Light, "text", Color::Rgb(40, 40, 48))
+            .with_variant_role(ThemeVariant::Light, "text.muted", Color::Rgb(110, 110, 120))
+            .with_variant_role(ThemeVariant::Light, "border", Color::Rgb(190, 190, 200))
+            .with_variant_role(ThemeVariant::Light, "border.focused", Color::Rgb(64, 120, 242))
+            .with_variant_role(ThemeVariant::Light, "error", Color::Rgb(202, 18, 67))
+    }
+}
+
+impl Theme {
+    /// Create a new theme without any roles.
+    pub fn new() -> Self {
+        Self {
+            roles: HashMap::new(),
+            dark: HashMap::new(),
+            light: HashMap::new(),
+            variant: ThemeVariant::default(),
+        }
+    }
+
+    /// Use the given variant's overrides.
+    pub fn with_variant(mut self, variant: ThemeVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Set the style for the given role, shared by both variants.
+    pub fn with_role<S: Into<Style>>(mut self, role: &str, style: S) -> Self {
+        self.set_role(role, style);
+        self
+    }
+
+    /// Set the style for the given role in one variant, applied on top of the shared style.
+    pub fn with_variant_role<S>(mut self, variant: ThemeVariant, role: &str, style: S) -> Self
+    where
+        S: Into<Style>,
+    {
+        self.set_variant_role(variant, role, style);
+        self
+    }
+
+    /// Set the style for the given role, shared by both variants.
+    pub fn set_role<S: Into<Style>>(&mut self, role: &str, style: S) {
+        self.roles.insert(role.to_string(), style.into());
+    }
+
+    /// Set the style for the given role in one variant, applied on top of the shared style.
+    pub fn set_variant_role<S>(&mut self, variant: ThemeVariant, role: &str, style: S)
+    where
+        S: Into<Style>,
+    {
+        self.variant_roles_mut(variant).insert(role.to_string(), style.into());
+    }
+
+    /// Resolve the style for the given role, including everything it inherits from its parents.
+    ///
+    /// Roles that aren't in the theme (at any level) resolve to an empty [`Style`].
+    pub fn style(&self, role: &str) -> Style {
+        let overrides = match self.variant {
+            ThemeVariant::Dark => &self.dark,
+            ThemeVariant::Light => &self.light,
+        };
+        let ends = role.match_indices('.').map(|(i, _)| i).chain([role.len()]);
+        let mut style = Style::new();
+        for end in ends {
+            let role = &role[..end];
+            for layer in [self.roles.get(role), overrides.get(role)].into_iter().flatten() {
+                style = style.patch(*layer);
+            }
+        }
+        style
+    }
+
+    fn variant_roles_mut(&mut self, variant: ThemeVariant) -> &mut HashMap<String, Style> {
+        match variant {
+            ThemeVariant::Dark => &mut self.dark,
+            ThemeVariant::Light => &mut self.light,
+        }
+    }
+}
+
+
+
+#[cfg(feature = "toml")]
+mod file {
+    use super::*;
+    use crate::{Modifier, ParseColorError};
+
+    /// An error encountered while loading a [`Theme`] from a file.
+    #[derive(Debug)]
+    pub enum ThemeError {
+        /// The file couldn't be read.
+        Io(std::io::Error),
+        /// The file isn't valid TOML.
+        Toml(toml::de::Error),
+        /// A color in the given role couldn't be parsed.
+        Color {
+            /// The role containing the color.
+            role: String,
+            /// Why the color couldn't be parsed.
+            error: ParseColorError,
+        },
+        /// The given key in the given role had a value of the wrong type, or an unknown name.
+        InvalidValue {
+            /// The role containing the value.
+            role: String,
+            /// The key of the value.
+            key: String,
+        },
+    }
+
+    impl std::fmt::Display for ThemeError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Io(error) => write!(f, "failed to read theme: {error}"),
+                Self::Toml(error) => write!(f, "invalid theme file: {error}"),
+                Self::Color { role, error } => write!(f, "invalid color in `{role}`: {error}"),
+                Self::InvalidValue { role, key } => write!(f, "invalid value for `{role}.{key}`"),
+            }
+        }
+    }
+
+    impl std::error::Error for ThemeError {}
+
+    impl Theme {
+        /// Load a theme from a TOML file. See [`Theme::from_toml`] for the format.
+        pub fn load_toml(path: impl AsRef<std::path::Path>) -> Result<Self, ThemeError> {
+            let contents = std::fs::read_to_string(path).map_err(ThemeError::Io)?;
+            Self::from_toml(&contents)
+        }
+
+        /// Parse a theme from a TOML string.
+        ///
+        /// Each role is a table, and nested tables are child roles. A role can set `fg`, `bg`, and
+        /// `underline_color` (in any format that [`Color`] can be parsed from), along with lists
+        /// of `modifiers` to add and `remove_modifiers` to remove. The `variants.dark` and
+        /// `variants.light` tables hold overrides for each variant, in the same format, so
+        /// `variants` can't be used as a role name.
+        ///
+        /// ```toml
+        /// [text]
+        /// fg = "#bfbfc5"
+        ///
+        /// [text.muted]
+        /// fg = "gray"
+        /// modifiers = ["italic"]
+        ///
+        /// [variants.light.text]
+        /// fg = "black"
+        /// ```
+        pub fn from_toml(contents: &str) -> Result<Self, ThemeError> {
+            let table = contents.parse::<toml::Table>().map_err(ThemeError::Toml)?;
+            let mut theme = Self::new();
+            for (key, value) in &table {
+                let table = value.as_table().ok_or_else(|| invalid("", key))?;
+                if key != "variants" {
+                    read_role(&mut theme.roles, key, table)?;
+                    continue;
+                }
+                for (name, value) in table {
+                    let variant = match name.as_str() {
+                        "dark" => ThemeVariant::Dark,
+                        "light" => ThemeVariant::Light,
+                        _ => return Err(invalid(key, name)),
+                    };
+                    let roles = value.as_table().ok_or_else(|| invalid(key, name))?;
+                    let parent = format!("{key}.{name}");
+                    for (role, value) in roles {
+                        let table = value.as_table().ok_or_else(|| invalid(&parent, role))?;
+                        read_role(theme.variant_roles_mut(variant), role, table)?;
+                    }
+                }
+            }
+            Ok(theme)
+        }
+    }
+
+    fn read_role(
+        roles: &mut HashMap<String, Style>,
+        role: &str,
+        table: &toml::Table,
+    ) -> Result<(), ThemeError> {
+        let mut style = Style::new();
+        let mut has_style = false;
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("fg" | "bg" | "underline_color", toml::Value::String(color)) => {
+                    let color = color.parse::<Color>().map_err(|error| ThemeError::Color {
+                        role: role.to_string(),
+                        error,
+                    })?;
+                    match key.as_str() {
+                        "fg" => style.fg = Some(color),
+                        "bg" => style.bg = Some(color),
+                        _ => style.underline_color = Some(color),
+                    }
+                    has_style = true;
+                }
+                ("modifiers" | "remove_modifiers", toml::Value::Array(names)) => {
+                    for name in names {
+                        let modifier = name.as_str()
+                            .and_then(|name| Modifier::from_name(&name.to_ascii_uppercase()))
+                            .ok_or_else(|| invalid(role, key))?;
+                        style = if key == "modifiers" {
+                            style.add_modifier(modifier)
+                        } else {
+                            style.remove_modifier(modifier)
+                        };
+                    }
+                    has_style = true;
+                }
+                (_, toml::Value::Table(child)) => {
+                    read_role(roles, &format!("{role}.{key}"), child)?;
+                }
+                _ => return Err(invalid(role, key)),
+            }
+        }
+        if has_style {
+            roles.insert(role.to_string(), style);
+        }
+        Ok(())
+    }
+
+    fn invalid(role: &str, key: &str) -> ThemeError {
+        ThemeError::InvalidValue {
+            role: role.to_string(),
+            key: key.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+pub use file::ThemeError;
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Modifier;
+
+    #[test]
+    fn role_inheritance() {
+        let theme = Theme::new()
+            .with_role("border", Style::new().fg(Color::Gray).bold())
+            .with_role("border.focused.error", Style::new().remove_modifier(Modifier::BOLD))
+            .with_variant_role(ThemeVariant::Dark, "border", Color::White)
+            .with_variant_role(ThemeVariant::Dark, "border.focused", Color::Blue);
+
+        assert_eq!(theme.style("border"), Style::new().fg(Color::White).bold());
+        assert_eq!(theme.style("border.focused"), Style::new().fg(Color::Blue).bold());
+        assert_eq!(
+            theme.style("border.focused.error"),
+            Style::new().fg(Color::Blue).remove_modifier(Modifier::BOLD),
+        );
+        assert_eq!(theme.style("missing.role"), Style::new());
+
+        let theme = theme.with_variant(ThemeVariant::Light);
+        assert_eq!(theme.style("border.focused"), Style::new().fg(Color::Gray).bold());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_themes() {
+        let theme = Theme::from_toml(r##"
+            [text]
+            fg = "#bfbfc5"
+
+            [text.muted]
+            modifiers = ["dim", "italic"]
+
+            [variants.light.text]
+            fg = "black"
+            bg = "rgb(250, 250, 250)"
+
+            # Roles can share a name with a variant.
+            [dark]
+            fg = "white"
+        "##).unwrap();
+
+        let muted = Style::new().fg(Color::Rgb(191, 191, 197)).dim().italic();
+        assert_eq!(theme.style("text.muted"), muted);
+        let theme = theme.with_variant(ThemeVariant::Light);
+        assert_eq!(theme.style("text").bg, Some(Color::Rgb(250, 250, 250)));
+        assert_eq!(theme.style("dark").fg, Some(Color::White));
+
+        let error = Theme::from_toml("[error]\nfg = \"#12\"").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid color in `error`: hex colors must have 3, 6, or 8 digits at position 1",
+        );
+        assert!(Theme::from_toml("[text]\nmodifiers = [\"shiny\"]").is_err());
+        assert_eq!(
+            Theme::from_toml("[variants.dim.text]\nfg = \"gray\"").unwrap_err().to_string(),
+            "invalid value for `variants.dim`",
+        );
+    }
+}